@@ -0,0 +1,387 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::future::Future;
+use std::io;
+use std::mem;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use bytes::Bytes;
+use tokio::io::AsyncWrite;
+
+use crate::async_object::AsyncObjectAPI;
+use crate::crc64::{self, Crc64};
+use crate::errors::{Error, ObjectError, ServiceErrorCode};
+use crate::object::AppendObjectResult;
+use crate::oss::{ObjectMeta, OSS};
+use crate::retry::RetryPolicy;
+
+/// Appends data to an appendable object, keeping track of its length.
+///
+/// The CRC64 of the object is computed along the way and checked against the
+/// one returned by every append. When an append is rejected with
+/// `PositionNotEqualToLength`, the object is headed again: an append which
+/// was retried after it had already been applied is taken as done, otherwise
+/// the object was appended by someone else and the data is appended after it.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn demo(oss: oss_rust_sdk::oss::OSS<'_>) -> Result<(), oss_rust_sdk::errors::Error> {
+/// use oss_rust_sdk::append::Appender;
+///
+/// let mut appender = Appender::resume(&oss, "logs/app.log")
+///     .await?
+///     .header("Content-Type", "text/plain");
+/// appender.append("started\n").await?;
+/// appender.append("stopped\n").await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Appender {
+    oss: OSS<'static>,
+    object_name: String,
+    position: u64,
+    crc64: Option<u64>,
+    headers: HashMap<String, String>,
+}
+
+impl Appender {
+    /// Append to a new object, starting at position 0. When the object
+    /// already exists the first append is rejected by OSS, and the data is then
+    /// appended after the existing content as described above; use
+    /// [`resume`](Appender::resume) to start from the end of an object.
+    pub fn new<S>(oss: &OSS, object_name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        let mut oss = oss.to_owned_client();
+        if oss.retry_policy().is_none() {
+            oss.set_retry_policy(Some(RetryPolicy::default()));
+        }
+        Appender {
+            oss,
+            object_name: object_name.into(),
+            position: 0,
+            crc64: Some(0),
+            headers: HashMap::new(),
+        }
+    }
+
+    /// Append to the end of an existing object, or to a new one when it does
+    /// not exist, heading the object to find its length and CRC64.
+    pub async fn resume<S>(oss: &OSS<'_>, object_name: S) -> Result<Self, Error>
+    where
+        S: Into<String>,
+    {
+        let mut appender = Self::new(oss, object_name);
+        match appender.oss.head_object(&appender.object_name).await {
+            Ok(meta) => appender.seek(&meta),
+            Err(e) if matches!(e.service_error_code(), Some(ServiceErrorCode::NoSuchKey)) => {}
+            Err(e) => return Err(e),
+        }
+        Ok(appender)
+    }
+
+    /// Retry policy of every append, instead of the one of the client.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.oss.set_retry_policy(Some(retry_policy));
+        self
+    }
+
+    /// Header sent with the append creating the object, e.g. `Content-Type`.
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn object_name(&self) -> &str {
+        &self.object_name
+    }
+
+    /// Position of the next append, which is the length of the object.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// CRC64 of the object, `None` when OSS did not return it.
+    pub fn crc64(&self) -> Option<u64> {
+        self.crc64
+    }
+
+    /// Write through an [`AsyncWrite`] appending every `buffer_size` bytes.
+    pub fn into_writer(self, buffer_size: usize) -> AppendWriter {
+        AppendWriter {
+            appender: Some(self),
+            appending: None,
+            buf: Vec::new(),
+            buffer_size: buffer_size.max(1),
+            failed: false,
+        }
+    }
+
+    pub async fn append<B>(&mut self, buf: B) -> Result<AppendObjectResult, Error>
+    where
+        B: Into<Bytes>,
+    {
+        let buf = buf.into();
+        match self.append_at_position(&buf).await {
+            Err(e)
+                if matches!(
+                    e.service_error_code(),
+                    Some(ServiceErrorCode::PositionNotEqualToLength)
+                ) =>
+            {
+                let meta = self.oss.head_object(&self.object_name).await?;
+                let length = meta.next_append_position.unwrap_or(meta.size as u64);
+                let expected = self.crc64_after(&buf);
+                if length == self.position + buf.len() as u64
+                    && (expected.is_none() || expected == meta.crc64)
+                {
+                    // a retry of an append which had already been applied
+                    self.seek(&meta);
+                    return Ok(AppendObjectResult {
+                        next_position: self.position,
+                        crc64: self.crc64,
+                    });
+                }
+                warn!(
+                    "{} was appended by someone else, append at {} instead of {}",
+                    self.object_name, length, self.position
+                );
+                self.seek(&meta);
+                self.append_at_position(&buf).await
+            }
+            result => result,
+        }
+    }
+
+    async fn append_at_position(&mut self, buf: &Bytes) -> Result<AppendObjectResult, Error> {
+        let headers = if self.position == 0 {
+            Some(self.headers.clone())
+        } else {
+            None
+        };
+        let result = self
+            .oss
+            .append_object(buf.clone(), &self.object_name, self.position, headers)
+            .await?;
+
+        let expected = self.crc64_after(buf);
+        // the data is appended even when it is corrupt, move past it so that
+        // the next append does not write it again
+        self.position = result.next_position;
+        self.crc64 = result.crc64.or(expected);
+        if let (Some(expected), Some(crc64)) = (expected, result.crc64) {
+            if expected != crc64 {
+                return Err(Error::Object(ObjectError::PostError {
+                    msg: format!(
+                        "CRC64 of {} is {}, expected {}, the corrupt data was appended up to {}",
+                        self.object_name, crc64, expected, self.position
+                    ),
+                }));
+            }
+        }
+        Ok(result)
+    }
+
+    /// CRC64 of the object once `buf` is appended to it.
+    fn crc64_after(&self, buf: &[u8]) -> Option<u64> {
+        let mut crc = Crc64::new();
+        crc.update(buf);
+        self.crc64
+            .map(|crc64| crc64::combine(crc64, crc.value(), buf.len() as u64))
+    }
+
+    fn seek(&mut self, meta: &ObjectMeta) {
+        self.position = meta.next_append_position.unwrap_or(meta.size as u64);
+        self.crc64 = meta.crc64;
+    }
+}
+
+type Appending =
+    Pin<Box<dyn Future<Output = (Appender, Result<AppendObjectResult, Error>)> + Send>>;
+
+/// An [`AsyncWrite`] appending to an object with an [`Appender`], one append
+/// every `buffer_size` bytes written. The rest is appended by `flush` and
+/// `shutdown`, call one of them before dropping the writer.
+///
+/// Once an append failed, every write fails: the data of the failed append
+/// is lost, get the appender back with [`into_inner`](AppendWriter::into_inner)
+/// to find where the object ends.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn demo(oss: oss_rust_sdk::oss::OSS<'_>) -> Result<(), Box<dyn std::error::Error>> {
+/// use oss_rust_sdk::append::Appender;
+/// use tokio::io::AsyncWriteExt;
+///
+/// let mut writer = Appender::resume(&oss, "logs/app.log")
+///     .await?
+///     .into_writer(1024 * 1024);
+/// writer.write_all(b"started\n").await?;
+/// writer.write_all(b"stopped\n").await?;
+/// writer.shutdown().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AppendWriter {
+    appender: Option<Appender>,
+    appending: Option<Appending>,
+    buf: Vec<u8>,
+    buffer_size: usize,
+    failed: bool,
+}
+
+impl AppendWriter {
+    /// The appender, `None` while an append is in flight. The data buffered
+    /// and not flushed yet is dropped.
+    pub fn into_inner(self) -> Option<Appender> {
+        self.appender
+    }
+
+    fn start_append(&mut self) {
+        let mut appender = self
+            .appender
+            .take()
+            .expect("an append is already in flight");
+        let buf = mem::replace(&mut self.buf, Vec::with_capacity(self.buffer_size));
+        self.appending = Some(Box::pin(async move {
+            let result = appender.append(buf).await;
+            (appender, result)
+        }));
+    }
+
+    /// Wait for the append in flight, if any.
+    fn poll_appending(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        if let Some(appending) = self.appending.as_mut() {
+            let (appender, result) = ready!(appending.as_mut().poll(cx));
+            self.appending = None;
+            self.appender = Some(appender);
+            if let Err(e) = result {
+                self.failed = true;
+                return Poll::Ready(Err(io::Error::other(e)));
+            }
+        }
+        if self.failed {
+            return Poll::Ready(Err(io::Error::other("an earlier append failed")));
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for AppendWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_appending(cx))?;
+        if this.buf.len() >= this.buffer_size {
+            this.start_append();
+            ready!(this.poll_appending(cx))?;
+        }
+        let n = buf.len().min(this.buffer_size - this.buf.len());
+        this.buf.extend_from_slice(&buf[..n]);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_appending(cx))?;
+        if !this.buf.is_empty() {
+            this.start_append();
+            ready!(this.poll_appending(cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl fmt::Debug for AppendWriter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AppendWriter")
+            .field("appender", &self.appender)
+            .field("appending", &self.appending.is_some())
+            .field("buffered", &self.buf.len())
+            .field("buffer_size", &self.buffer_size)
+            .field("failed", &self.failed)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{self, Response};
+    use std::sync::{Arc, Mutex};
+    use tokio::io::AsyncWriteExt;
+
+    /// Client of a local stand-in of OSS supporting AppendObject, which
+    /// corrupts the data of the appends at the positions in `corrupt`. Returns the
+    /// client and the content of the object.
+    fn append_server(corrupt: Vec<u64>) -> (OSS<'static>, Arc<Mutex<Vec<u8>>>) {
+        let object = Arc::new(Mutex::new(Vec::new()));
+        let content = object.clone();
+        let url = test_server::serve(move |request| {
+            let mut object = content.lock().unwrap();
+            let position: u64 = request.query("position").unwrap().parse().unwrap();
+            if request.method != "POST" || position != object.len() as u64 {
+                return Response::error(409, "PositionNotEqualToLength");
+            }
+            let mut body = request.body;
+            if corrupt.contains(&position) {
+                if let Some(last) = body.last_mut() {
+                    *last ^= 1;
+                }
+            }
+            object.extend_from_slice(&body);
+            let mut crc = Crc64::new();
+            crc.update(&object);
+            Response::ok("")
+                .header("x-oss-next-append-position", object.len())
+                .header("x-oss-hash-crc64ecma", crc.value())
+        });
+        (test_server::client(&url), object)
+    }
+
+    #[tokio::test]
+    async fn writer_appends_every_buffer() {
+        let (oss, object) = append_server(Vec::new());
+        let mut writer = Appender::new(&oss, "app.log").into_writer(4);
+
+        writer.write_all(b"0123456789").await.unwrap();
+        assert_eq!(object.lock().unwrap().as_slice(), b"01234567");
+        writer.shutdown().await.unwrap();
+        assert_eq!(object.lock().unwrap().as_slice(), b"0123456789");
+
+        let appender = writer.into_inner().unwrap();
+        assert_eq!(appender.position(), 10);
+        let mut crc = Crc64::new();
+        crc.update(b"0123456789");
+        assert_eq!(appender.crc64(), Some(crc.value()));
+    }
+
+    #[tokio::test]
+    async fn corrupt_append_is_not_written_again() {
+        let (oss, object) = append_server(vec![0]);
+        let mut appender = Appender::new(&oss, "app.log");
+
+        let msg = appender.append("first").await.unwrap_err().to_string();
+        assert!(msg.contains("appended up to 5"), "{}", msg);
+        assert_eq!(appender.position(), 5);
+
+        appender.append("second").await.unwrap();
+        assert_eq!(object.lock().unwrap().as_slice(), b"firsusecond");
+    }
+}
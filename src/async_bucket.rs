@@ -0,0 +1,166 @@
+use async_trait::async_trait;
+
+use crate::bucket::{
+    bucket_request, parse_bucket_location, put_bucket_config_request, put_bucket_request,
+    BucketInfo, BucketStat, PutBucketOptions,
+};
+use crate::cors::CorsConfiguration;
+use crate::lifecycle::LifecycleConfiguration;
+use crate::policy::PolicyDocument;
+use crate::versioning::VersioningConfiguration;
+
+use super::errors::Error;
+use super::oss::{RequestType, OSS};
+
+#[async_trait]
+pub trait AsyncBucketAPI {
+    /// Create the bucket of the client.
+    async fn put_bucket(&self, options: PutBucketOptions) -> Result<(), Error>;
+
+    /// Delete the bucket of the client, which must be empty.
+    async fn delete_bucket(&self) -> Result<(), Error>;
+
+    async fn get_bucket_info(&self) -> Result<BucketInfo, Error>;
+
+    /// Region of the bucket, e.g. `oss-cn-hangzhou`.
+    async fn get_bucket_location(&self) -> Result<String, Error>;
+
+    async fn get_bucket_stat(&self) -> Result<BucketStat, Error>;
+
+    /// Replace the lifecycle rules of the bucket.
+    async fn put_bucket_lifecycle(&self, config: &LifecycleConfiguration) -> Result<(), Error>;
+
+    async fn get_bucket_lifecycle(&self) -> Result<LifecycleConfiguration, Error>;
+
+    async fn delete_bucket_lifecycle(&self) -> Result<(), Error>;
+
+    /// Replace the CORS rules of the bucket.
+    async fn put_bucket_cors(&self, config: &CorsConfiguration) -> Result<(), Error>;
+
+    async fn get_bucket_cors(&self) -> Result<CorsConfiguration, Error>;
+
+    async fn delete_bucket_cors(&self) -> Result<(), Error>;
+
+    /// Replace the policy of the bucket.
+    async fn put_bucket_policy(&self, policy: &PolicyDocument) -> Result<(), Error>;
+
+    async fn get_bucket_policy(&self) -> Result<PolicyDocument, Error>;
+
+    async fn delete_bucket_policy(&self) -> Result<(), Error>;
+
+    /// Enable or suspend versioning of the bucket.
+    async fn put_bucket_versioning(&self, config: &VersioningConfiguration) -> Result<(), Error>;
+
+    async fn get_bucket_versioning(&self) -> Result<VersioningConfiguration, Error>;
+}
+
+#[async_trait]
+impl<'a> AsyncBucketAPI for OSS<'a> {
+    async fn put_bucket(&self, options: PutBucketOptions) -> Result<(), Error> {
+        self.send(put_bucket_request(options)?).await?;
+        Ok(())
+    }
+
+    async fn delete_bucket(&self) -> Result<(), Error> {
+        self.send(bucket_request(RequestType::Delete, None)).await?;
+        Ok(())
+    }
+
+    async fn get_bucket_info(&self) -> Result<BucketInfo, Error> {
+        let resp = self
+            .send(bucket_request(RequestType::Get, Some("bucketInfo")))
+            .await?;
+
+        BucketInfo::from_xml(&resp.text().await?)
+    }
+
+    async fn get_bucket_location(&self) -> Result<String, Error> {
+        let resp = self
+            .send(bucket_request(RequestType::Get, Some("location")))
+            .await?;
+
+        parse_bucket_location(&resp.text().await?)
+    }
+
+    async fn get_bucket_stat(&self) -> Result<BucketStat, Error> {
+        let resp = self
+            .send(bucket_request(RequestType::Get, Some("stat")))
+            .await?;
+
+        BucketStat::from_xml(&resp.text().await?)
+    }
+
+    async fn put_bucket_lifecycle(&self, config: &LifecycleConfiguration) -> Result<(), Error> {
+        self.send(put_bucket_config_request("lifecycle", config.to_xml()?))
+            .await?;
+        Ok(())
+    }
+
+    async fn get_bucket_lifecycle(&self) -> Result<LifecycleConfiguration, Error> {
+        let resp = self
+            .send(bucket_request(RequestType::Get, Some("lifecycle")))
+            .await?;
+
+        LifecycleConfiguration::from_xml(&resp.text().await?)
+    }
+
+    async fn delete_bucket_lifecycle(&self) -> Result<(), Error> {
+        self.send(bucket_request(RequestType::Delete, Some("lifecycle")))
+            .await?;
+        Ok(())
+    }
+
+    async fn put_bucket_cors(&self, config: &CorsConfiguration) -> Result<(), Error> {
+        self.send(put_bucket_config_request("cors", config.to_xml()?))
+            .await?;
+        Ok(())
+    }
+
+    async fn get_bucket_cors(&self) -> Result<CorsConfiguration, Error> {
+        let resp = self
+            .send(bucket_request(RequestType::Get, Some("cors")))
+            .await?;
+
+        CorsConfiguration::from_xml(&resp.text().await?)
+    }
+
+    async fn delete_bucket_cors(&self) -> Result<(), Error> {
+        self.send(bucket_request(RequestType::Delete, Some("cors")))
+            .await?;
+        Ok(())
+    }
+
+    async fn put_bucket_policy(&self, policy: &PolicyDocument) -> Result<(), Error> {
+        self.send(put_bucket_config_request("policy", policy.to_json()?))
+            .await?;
+        Ok(())
+    }
+
+    async fn get_bucket_policy(&self) -> Result<PolicyDocument, Error> {
+        let resp = self
+            .send(bucket_request(RequestType::Get, Some("policy")))
+            .await?;
+
+        PolicyDocument::from_json(&resp.text().await?)
+    }
+
+    async fn delete_bucket_policy(&self) -> Result<(), Error> {
+        self.send(bucket_request(RequestType::Delete, Some("policy")))
+            .await?;
+        Ok(())
+    }
+
+    async fn put_bucket_versioning(&self, config: &VersioningConfiguration) -> Result<(), Error> {
+        self.send(put_bucket_config_request("versioning", config.to_xml()?))
+            .await?;
+        Ok(())
+    }
+
+    async fn get_bucket_versioning(&self) -> Result<VersioningConfiguration, Error> {
+        let resp = self
+            .send(bucket_request(RequestType::Get, Some("versioning")))
+            .await?;
+
+        VersioningConfiguration::from_xml(&resp.text().await?)
+    }
+}
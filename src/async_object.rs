@@ -1,15 +1,88 @@
 use std::collections::HashMap;
+use std::error::Error as StdError;
+use std::io;
+#[cfg(feature = "fs")]
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
 
 use crate::{
-    multi_part::{CompleteMultipartUploadResult, InitiateMultipartUploadResult},
+    multi_part::{CompleteMultipartUploadResult, InitiateMultipartUploadResult, ListPartsResult},
+    object::{
+        append_object_request, check_delete_prefix, delete_objects_request, list_v2_resources,
+        object_request, parse_object_acl, put_symlink_request, symlink_target, upload_part_e_tag,
+        AppendObjectResult, DeleteObjectsResult, DeletePrefixResult, ListEntry, ListObjectsV2,
+        PrivateObjectAPI, DELETE_OBJECTS_BATCH,
+    },
+    options::{CopyObjectOptions, GetObjectOptions, ObjectOptions, PutObjectOptions},
     oss::{ObjectMeta, RequestType},
     prelude::{ListObjects, OSS},
+    request::Request,
+    restore::{restore_object_request, RestoreRequest},
+    tagging::{tagging_request, Tagging},
+    versioning::{version_id, DeleteObjectResult, ListObjectVersions},
 };
 
-use super::errors::{Error, ObjectError};
+use super::errors::Error;
 
 use async_trait::async_trait;
 use bytes::Bytes;
+use futures_util::{stream, Stream, TryStreamExt};
+use reqwest::header::HeaderMap;
+use tokio::io::AsyncRead;
+#[cfg(feature = "fs")]
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::{ReaderStream, StreamReader};
+
+/// Body of an object being downloaded, along with the response headers.
+pub struct ObjectStream {
+    headers: HeaderMap,
+    inner: Pin<Box<dyn Stream<Item = Result<Bytes, Error>> + Send>>,
+}
+
+impl ObjectStream {
+    fn new(resp: reqwest::Response) -> Self {
+        ObjectStream {
+            headers: resp.headers().clone(),
+            inner: Box::pin(resp.bytes_stream().map_err(Error::from)),
+        }
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    pub fn meta(&self) -> Result<ObjectMeta, Error> {
+        ObjectMeta::from_header_map(&self.headers)
+    }
+
+    /// Read the body through `AsyncRead` instead of as a stream of chunks.
+    pub fn into_async_read(self) -> impl AsyncRead + Send + Unpin {
+        StreamReader::new(self.inner.map_err(io::Error::other))
+    }
+}
+
+impl Stream for ObjectStream {
+    type Item = Result<Bytes, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
+
+/// Every object and common prefix of a ListObjectsV2 listing, the next page
+/// being requested when the current one is exhausted.
+pub struct ListObjectsV2Stream<'b> {
+    inner: Pin<Box<dyn Stream<Item = Result<ListEntry, Error>> + Send + 'b>>,
+}
+
+impl<'b> Stream for ListObjectsV2Stream<'b> {
+    type Item = Result<ListEntry, Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.inner.as_mut().poll_next(cx)
+    }
+}
 
 #[async_trait]
 pub trait AsyncObjectAPI {
@@ -19,6 +92,40 @@ pub trait AsyncObjectAPI {
         H: Into<Option<HashMap<S, S>>> + Send,
         R: Into<Option<HashMap<S, Option<S>>>> + Send;
 
+    /// List objects with ListObjectsV2, `list-type=2` is added to the resources.
+    /// Pass `continuation-token`, `start-after`, `fetch-owner` or `encoding-type`
+    /// as resources.
+    async fn list_objects_v2<S, H, R>(
+        &self,
+        headers: H,
+        resources: R,
+    ) -> Result<ListObjectsV2, Error>
+    where
+        S: AsRef<str>,
+        H: Into<Option<HashMap<S, S>>> + Send,
+        R: Into<Option<HashMap<S, Option<S>>>> + Send;
+
+    /// Stream every object and common prefix with ListObjectsV2, following
+    /// `next-continuation-token` until the listing is complete.
+    fn list_objects_v2_stream<S, H, R>(&self, headers: H, resources: R) -> ListObjectsV2Stream<'_>
+    where
+        S: AsRef<str>,
+        H: Into<Option<HashMap<S, S>>>,
+        R: Into<Option<HashMap<S, Option<S>>>>;
+
+    /// List the versions and delete markers of the objects, `versions` is added
+    /// to the resources. Pass `prefix`, `delimiter`, `key-marker`,
+    /// `version-id-marker`, `max-keys` or `encoding-type` as resources.
+    async fn list_object_versions<S, H, R>(
+        &self,
+        headers: H,
+        resources: R,
+    ) -> Result<ListObjectVersions, Error>
+    where
+        S: AsRef<str>,
+        H: Into<Option<HashMap<S, S>>> + Send,
+        R: Into<Option<HashMap<S, Option<S>>>> + Send;
+
     async fn get_object<S1, S2, H, R>(
         &self,
         object_name: S1,
@@ -31,19 +138,101 @@ pub trait AsyncObjectAPI {
         H: Into<Option<HashMap<S2, S2>>> + Send,
         R: Into<Option<HashMap<S2, Option<S2>>>> + Send;
 
-    async fn put_object<S1, S2, H, R>(
+    /// Get an object without buffering it, the body is read chunk by chunk
+    /// from the returned stream.
+    async fn get_object_stream<S1, S2, H, R>(
+        &self,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<ObjectStream, Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send;
+
+    /// Download an object into `file`, writing it as it is received.
+    #[cfg(feature = "fs")]
+    async fn get_object_to_file<S1, S2, S3, H, R>(
+        &self,
+        object_name: S1,
+        file: S2,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<Path> + Send,
+        S3: AsRef<str> + Send,
+        H: Into<Option<HashMap<S3, S3>>> + Send,
+        R: Into<Option<HashMap<S3, Option<S3>>>> + Send;
+
+    async fn put_object<B, S1, S2, H, R>(
         &self,
-        buf: &[u8],
+        buf: B,
         object_name: S1,
         headers: H,
         resources: R,
     ) -> Result<(), Error>
     where
+        B: Into<Bytes> + Send,
         S1: AsRef<str> + Send,
         S2: AsRef<str> + Send,
         H: Into<Option<HashMap<S2, S2>>> + Send,
         R: Into<Option<HashMap<S2, Option<S2>>>> + Send;
 
+    /// Upload a file, streaming it from disk rather than loading it in memory.
+    #[cfg(feature = "fs")]
+    async fn put_object_from_file<S1, S2, S3, H, R>(
+        &self,
+        file: S1,
+        object_name: S2,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        S3: AsRef<str> + Send,
+        H: Into<Option<HashMap<S3, S3>>> + Send,
+        R: Into<Option<HashMap<S3, Option<S3>>>> + Send;
+
+    /// Upload the `len` bytes read from `reader`. The body can not be replayed,
+    /// so the request is never retried.
+    async fn put_object_from_reader<S1, S2, H, R, Rd>(
+        &self,
+        reader: Rd,
+        len: u64,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
+        Rd: AsyncRead + Send + Sync + 'static;
+
+    /// Upload the `len` bytes yielded by `stream`. The body can not be replayed,
+    /// so the request is never retried.
+    async fn put_object_from_stream<S1, S2, H, R, St, E>(
+        &self,
+        stream: St,
+        len: u64,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
+        St: Stream<Item = Result<Bytes, E>> + Send + Sync + 'static,
+        E: Into<Box<dyn StdError + Send + Sync>> + 'static;
+
     async fn copy_object_from_object<S1, S2, S3, H, R>(
         &self,
         src: S1,
@@ -58,14 +247,189 @@ pub trait AsyncObjectAPI {
         H: Into<Option<HashMap<S3, S3>>> + Send,
         R: Into<Option<HashMap<S3, Option<S3>>>> + Send;
 
+    async fn get_object_acl<S>(&self, object_name: S) -> Result<String, Error>
+    where
+        S: AsRef<str> + Send;
+
+    /// `get_object_acl` with typed options, e.g. of a previous version.
+    async fn get_object_acl_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<String, Error>
+    where
+        S: AsRef<str> + Send;
+
+    /// Presigned url to GET the object until the unix time `expires`. The url
+    /// is built locally, no request is sent.
+    fn try_get_object_signed_url<S>(&self, object_name: S, expires: usize) -> Result<String, Error>
+    where
+        S: AsRef<str> + Send;
+
+    /// `get_object` with typed options.
+    async fn get_object_with_options<S>(
+        &self,
+        object_name: S,
+        options: GetObjectOptions,
+    ) -> Result<Bytes, Error>
+    where
+        S: AsRef<str> + Send;
+
+    /// `put_object` with typed options, returning the version id of the
+    /// object on versioned buckets.
+    async fn put_object_with_options<B, S>(
+        &self,
+        buf: B,
+        object_name: S,
+        options: PutObjectOptions,
+    ) -> Result<Option<String>, Error>
+    where
+        B: Into<Bytes> + Send,
+        S: AsRef<str> + Send;
+
     async fn delete_object<S>(&self, object_name: S) -> Result<(), Error>
     where
         S: AsRef<str> + Send;
 
+    /// `delete_object` with typed options. With a version id the version is
+    /// permanently deleted, or the delete marker removed to restore the object.
+    async fn delete_object_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<DeleteObjectResult, Error>
+    where
+        S: AsRef<str> + Send;
+
+    /// Delete several objects with DeleteMultipleObjects, in batches of
+    /// [`DELETE_OBJECTS_BATCH`] keys. The batches before a failed one are
+    /// already deleted when an error is returned.
+    async fn delete_objects<S, I>(
+        &self,
+        keys: I,
+        quiet: bool,
+    ) -> Result<DeleteObjectsResult, Error>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = S> + Send;
+
+    /// Delete every object whose key starts with `prefix`, which can not be
+    /// empty, with quiet requests so that only the failed keys are kept.
+    async fn delete_prefix<S>(&self, prefix: S) -> Result<DeletePrefixResult, Error>
+    where
+        S: AsRef<str> + Send;
+
     async fn head_object<S>(&self, object_name: S) -> Result<ObjectMeta, Error>
     where
         S: AsRef<str> + Send;
 
+    /// `head_object` with typed options, e.g. of a previous version.
+    async fn head_object_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<ObjectMeta, Error>
+    where
+        S: AsRef<str> + Send;
+
+    /// Append `buf` to an appendable object at `position`, which must be its
+    /// current length, 0 creating the object.
+    ///
+    /// [`Appender`](crate::append::Appender) keeps track of the position.
+    async fn append_object<B, S1, S2, H>(
+        &self,
+        buf: B,
+        object_name: S1,
+        position: u64,
+        headers: H,
+    ) -> Result<AppendObjectResult, Error>
+    where
+        B: Into<Bytes> + Send,
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send;
+
+    /// Create `link` as a symlink to the object `target`, `options` setting
+    /// the storage class, ACL or metadata of the link. Returns the version id
+    /// of the link on versioned buckets.
+    async fn put_symlink<S1, S2>(
+        &self,
+        link: S1,
+        target: S2,
+        options: PutObjectOptions,
+    ) -> Result<Option<String>, Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send;
+
+    /// The object the symlink `link` points to.
+    async fn get_symlink<S>(&self, link: S) -> Result<String, Error>
+    where
+        S: AsRef<str> + Send;
+
+    /// Restore an archived object, see [`restore`](crate::restore) to wait
+    /// until it is readable.
+    async fn restore_object<S>(
+        &self,
+        object_name: S,
+        request: &RestoreRequest,
+    ) -> Result<(), Error>
+    where
+        S: AsRef<str> + Send;
+
+    /// `copy_object_from_object` with typed options, returning the version id
+    /// of `dest` on versioned buckets.
+    async fn copy_object_with_options<S1, S2>(
+        &self,
+        src: S1,
+        dest: S2,
+        options: CopyObjectOptions,
+    ) -> Result<Option<String>, Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send;
+
+    /// Replace the tags of an object.
+    async fn put_object_tagging<S>(&self, object_name: S, tagging: &Tagging) -> Result<(), Error>
+    where
+        S: AsRef<str> + Send;
+
+    async fn get_object_tagging<S>(&self, object_name: S) -> Result<Tagging, Error>
+    where
+        S: AsRef<str> + Send;
+
+    async fn delete_object_tagging<S>(&self, object_name: S) -> Result<(), Error>
+    where
+        S: AsRef<str> + Send;
+
+    /// `put_object_tagging` with typed options, e.g. to tag a previous version.
+    async fn put_object_tagging_with_options<S>(
+        &self,
+        object_name: S,
+        tagging: &Tagging,
+        options: ObjectOptions,
+    ) -> Result<(), Error>
+    where
+        S: AsRef<str> + Send;
+
+    /// `get_object_tagging` with typed options, e.g. of a previous version.
+    async fn get_object_tagging_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<Tagging, Error>
+    where
+        S: AsRef<str> + Send;
+
+    /// `delete_object_tagging` with typed options, e.g. of a previous version.
+    async fn delete_object_tagging_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<(), Error>
+    where
+        S: AsRef<str> + Send;
+
     /// Notify oss to init a Multipart Upload event
     async fn init_multi<S1, S2, H, R>(
         &self,
@@ -80,13 +444,63 @@ pub trait AsyncObjectAPI {
         R: Into<Option<HashMap<S2, Option<S2>>>> + Send;
 
     /// Upload data in chunks according to the specified Object name and uploadId
-    async fn upload_part<S1, S2, H, R>(
+    async fn upload_part<B, S1, S2, H, R>(
+        &self,
+        buf: B,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<String, Error>
+    where
+        B: Into<Bytes> + Send,
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send;
+
+    /// `upload_part` of the `len` bytes read from `reader`, without loading
+    /// the part in memory. The body can not be replayed, so the request is
+    /// never retried.
+    async fn upload_part_from_reader<S1, S2, H, R, Rd>(
+        &self,
+        reader: Rd,
+        len: u64,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<String, Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
+        Rd: AsyncRead + Send + Sync + 'static;
+
+    /// `upload_part` of the `len` bytes yielded by `stream`. The body can not
+    /// be replayed, so the request is never retried.
+    async fn upload_part_from_stream<S1, S2, H, R, St, E>(
         &self,
-        buf: &[u8],
+        stream: St,
+        len: u64,
         object_name: S1,
         headers: H,
         resources: R,
     ) -> Result<String, Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
+        St: Stream<Item = Result<Bytes, E>> + Send + Sync + 'static,
+        E: Into<Box<dyn StdError + Send + Sync>> + 'static;
+
+    /// List the parts uploaded so far, up to `max-parts` from `part-number-marker`
+    async fn list_parts<S1, S2, H, R>(
+        &self,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<ListPartsResult, Error>
     where
         S1: AsRef<str> + Send,
         S2: AsRef<str> + Send,
@@ -106,28 +520,15 @@ pub trait AsyncObjectAPI {
     ///
     /// # Examples
     ///
-    ///  #[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
-    ///  #[serde(rename_all = "PascalCase")]
-    ///  pub struct PartWrapper {
-    ///      pub part: Vec<Part>,
-    ///  }
-    ///
-    ///  #[derive(Debug, Serialize, Deserialize, PartialEq)]
-    ///  #[serde(rename_all = "PascalCase")]
-    ///  pub struct Part {
-    ///      part_number: usize,
-    ///      e_tag: String,
-    ///  }
+    ///  let parts = CompleteMultipartUpload::new(vec![Part {
+    ///      part_number: 1,
+    ///      e_tag: "50BE5FACC702C5B945588031C6*****".to_string(),
+    ///  }]);
     ///
-    ///  let parts = CompleteDTO {
-    ///      part: vec![Part {
-    ///          part_number: 1,
-    ///          e_tag: "50BE5FACC702C5B945588031C6*****".to_string(),
-    ///      }],
-    ///  };
-    ///
-    ///  let body = quick_xml::se::to_string_with_root("CompleteMultipartUpload", &parts).unwrap();
+    ///  let body = parts.to_xml()?;
     ///
+    /// [`MultipartUploader`](crate::multi_part::MultipartUploader) drives the
+    /// whole upload instead.
     async fn complete_multi<S1, S2, H, R>(
         &self,
         body: String,
@@ -163,151 +564,593 @@ impl<'a> AsyncObjectAPI for OSS<'a> {
         H: Into<Option<HashMap<S, S>>> + Send,
         R: Into<Option<HashMap<S, Option<S>>>> + Send,
     {
-        let (host, headers) =
-            self.build_request(RequestType::Get, String::new(), headers, resources)?;
+        let resp = self
+            .send(Request::new(
+                RequestType::Get,
+                String::new(),
+                headers,
+                resources,
+            ))
+            .await?;
 
-        let resp = self.http_client.get(host).headers(headers).send().await?;
         let body = resp.text().await?;
         let list_objects = quick_xml::de::from_str::<ListObjects>(&body)?;
 
         Ok(list_objects)
     }
 
-    async fn get_object<S1, S2, H, R>(
+    async fn list_objects_v2<S, H, R>(
         &self,
-        object_name: S1,
         headers: H,
         resources: R,
-    ) -> Result<Bytes, Error>
+    ) -> Result<ListObjectsV2, Error>
     where
-        S1: AsRef<str> + Send,
-        S2: AsRef<str> + Send,
-        H: Into<Option<HashMap<S2, S2>>> + Send,
-        R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
+        S: AsRef<str>,
+        H: Into<Option<HashMap<S, S>>> + Send,
+        R: Into<Option<HashMap<S, Option<S>>>> + Send,
+    {
+        let request = Request::new(RequestType::Get, String::new(), headers, resources)
+            .resource("list-type", Some("2"));
+        let resp = self.send(request).await?;
+
+        let body = resp.text().await?;
+        ListObjectsV2::from_xml(&body)
+    }
+
+    async fn list_object_versions<S, H, R>(
+        &self,
+        headers: H,
+        resources: R,
+    ) -> Result<ListObjectVersions, Error>
+    where
+        S: AsRef<str>,
+        H: Into<Option<HashMap<S, S>>> + Send,
+        R: Into<Option<HashMap<S, Option<S>>>> + Send,
     {
-        let (host, headers) =
-            self.build_request(RequestType::Get, object_name, headers, resources)?;
+        let request = Request::new(RequestType::Get, String::new(), headers, resources)
+            .resource("versions", None::<String>);
+        let resp = self.send(request).await?;
+
+        ListObjectVersions::from_xml(&resp.text().await?)
+    }
 
-        let resp = self.http_client.get(&host).headers(headers).send().await?;
+    fn list_objects_v2_stream<S, H, R>(&self, headers: H, resources: R) -> ListObjectsV2Stream<'_>
+    where
+        S: AsRef<str>,
+        H: Into<Option<HashMap<S, S>>>,
+        R: Into<Option<HashMap<S, Option<S>>>>,
+    {
+        let request = Request::new(RequestType::Get, String::new(), headers, resources);
+        let headers = request.headers().clone();
+        let resources = request.resources().clone();
 
-        if resp.status().is_success() {
-            Ok(resp.bytes().await?)
-        } else {
-            Err(Error::Object(ObjectError::GetError {
-                msg: format!("can not get object, status code: {}", resp.status()).into(),
-            }))
+        // `None` once the last page was listed
+        let pages = stream::try_unfold(Some(None), move |token: Option<Option<String>>| {
+            let headers = headers.clone();
+            let resources =
+                list_v2_resources(&resources, token.as_ref().and_then(|t| t.as_deref()));
+            async move {
+                if token.is_none() {
+                    return Ok::<_, Error>(None);
+                }
+                let page = self.list_objects_v2(Some(headers), Some(resources)).await?;
+                let next = if page.is_truncated() && !page.next_continuation_token().is_empty() {
+                    Some(Some(page.next_continuation_token().to_string()))
+                } else {
+                    None
+                };
+                Ok(Some((
+                    stream::iter(page.into_entries().into_iter().map(Ok::<_, Error>)),
+                    next,
+                )))
+            }
+        });
+        ListObjectsV2Stream {
+            inner: Box::pin(pages.try_flatten()),
         }
     }
 
-    async fn put_object<S1, S2, H, R>(
+    async fn get_object<S1, S2, H, R>(
         &self,
-        buf: &[u8],
         object_name: S1,
         headers: H,
         resources: R,
-    ) -> Result<(), Error>
+    ) -> Result<Bytes, Error>
     where
         S1: AsRef<str> + Send,
         S2: AsRef<str> + Send,
         H: Into<Option<HashMap<S2, S2>>> + Send,
         R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
     {
-        let (host, headers) =
-            self.build_request(RequestType::Put, object_name, headers, resources)?;
-
         let resp = self
-            .http_client
-            .put(&host)
-            .headers(headers)
-            .body(buf.to_owned())
-            .send()
+            .send(Request::new(
+                RequestType::Get,
+                object_name,
+                headers,
+                resources,
+            ))
             .await?;
 
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(Error::Object(ObjectError::DeleteError {
-                msg: format!(
-                    "can not put object, status code, status code: {}",
-                    resp.status()
-                )
-                .into(),
-            }))
-        }
+        Ok(resp.bytes().await?)
     }
 
-    async fn copy_object_from_object<S1, S2, S3, H, R>(
+    async fn get_object_stream<S1, S2, H, R>(
         &self,
-        src: S1,
-        dest: S2,
+        object_name: S1,
         headers: H,
         resources: R,
-    ) -> Result<(), Error>
+    ) -> Result<ObjectStream, Error>
     where
         S1: AsRef<str> + Send,
         S2: AsRef<str> + Send,
-        S3: AsRef<str> + Send,
-        H: Into<Option<HashMap<S3, S3>>> + Send,
-        R: Into<Option<HashMap<S3, Option<S3>>>> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
     {
-        let (host, mut headers) = self.build_request(RequestType::Put, dest, headers, resources)?;
-        headers.insert("x-oss-copy-source", src.as_ref().parse()?);
+        let resp = self
+            .send(Request::new(
+                RequestType::Get,
+                object_name,
+                headers,
+                resources,
+            ))
+            .await?;
+
+        Ok(ObjectStream::new(resp))
+    }
 
-        let resp = self.http_client.put(&host).headers(headers).send().await?;
+    #[cfg(feature = "fs")]
+    async fn get_object_to_file<S1, S2, S3, H, R>(
+        &self,
+        object_name: S1,
+        file: S2,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<Path> + Send,
+        S3: AsRef<str> + Send,
+        H: Into<Option<HashMap<S3, S3>>> + Send,
+        R: Into<Option<HashMap<S3, Option<S3>>>> + Send,
+    {
+        let mut stream = self
+            .get_object_stream(object_name, headers, resources)
+            .await?;
 
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(Error::Object(ObjectError::CopyError {
-                msg: format!("can not copy object, status code: {}", resp.status()).into(),
-            }))
+        let mut f = tokio::fs::File::create(file).await?;
+        while let Some(chunk) = stream.try_next().await? {
+            f.write_all(&chunk).await?;
         }
+        f.flush().await?;
+        Ok(())
+    }
+
+    async fn put_object<B, S1, S2, H, R>(
+        &self,
+        buf: B,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        B: Into<Bytes> + Send,
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
+    {
+        self.send(Request::new(RequestType::Put, object_name, headers, resources).body(buf))
+            .await?;
+        Ok(())
+    }
+
+    #[cfg(feature = "fs")]
+    async fn put_object_from_file<S1, S2, S3, H, R>(
+        &self,
+        file: S1,
+        object_name: S2,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        S3: AsRef<str> + Send,
+        H: Into<Option<HashMap<S3, S3>>> + Send,
+        R: Into<Option<HashMap<S3, Option<S3>>>> + Send,
+    {
+        let request = Request::new(RequestType::Put, object_name, headers, resources);
+        let len = tokio::fs::metadata(file.as_ref()).await?.len();
+
+        self.send(request.file_body(file.as_ref(), len)).await?;
+        Ok(())
+    }
+
+    async fn put_object_from_reader<S1, S2, H, R, Rd>(
+        &self,
+        reader: Rd,
+        len: u64,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
+        Rd: AsyncRead + Send + Sync + 'static,
+    {
+        self.put_object_from_stream(
+            ReaderStream::new(reader),
+            len,
+            object_name,
+            headers,
+            resources,
+        )
+        .await
+    }
+
+    async fn put_object_from_stream<S1, S2, H, R, St, E>(
+        &self,
+        stream: St,
+        len: u64,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
+        St: Stream<Item = Result<Bytes, E>> + Send + Sync + 'static,
+        E: Into<Box<dyn StdError + Send + Sync>> + 'static,
+    {
+        self.send(
+            Request::new(RequestType::Put, object_name, headers, resources)
+                .stream_body(reqwest::Body::wrap_stream(stream), len),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn copy_object_from_object<S1, S2, S3, H, R>(
+        &self,
+        src: S1,
+        dest: S2,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        S3: AsRef<str> + Send,
+        H: Into<Option<HashMap<S3, S3>>> + Send,
+        R: Into<Option<HashMap<S3, Option<S3>>>> + Send,
+    {
+        self.send(
+            Request::new(RequestType::Put, dest, headers, resources)
+                .header("x-oss-copy-source", src.as_ref()),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn get_object_acl<S>(&self, object_name: S) -> Result<String, Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        self.get_object_acl_with_options(object_name, ObjectOptions::new())
+            .await
+    }
+
+    async fn get_object_acl_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<String, Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        let resp = self
+            .send(
+                object_request(RequestType::Get, object_name, options)
+                    .resource("acl", None::<String>),
+            )
+            .await?;
+
+        parse_object_acl(&resp.text().await?)
+    }
+
+    fn try_get_object_signed_url<S>(&self, object_name: S, expires: usize) -> Result<String, Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        Ok(format!(
+            "https://{}.{}{}",
+            self.bucket(),
+            self.endpoint(),
+            self.generate_presigned_path(object_name, expires)?,
+        ))
+    }
+
+    async fn get_object_with_options<S>(
+        &self,
+        object_name: S,
+        options: GetObjectOptions,
+    ) -> Result<Bytes, Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        let (headers, resources) = options.into_parts();
+        self.get_object(object_name, headers, resources).await
+    }
+
+    async fn put_object_with_options<B, S>(
+        &self,
+        buf: B,
+        object_name: S,
+        options: PutObjectOptions,
+    ) -> Result<Option<String>, Error>
+    where
+        B: Into<Bytes> + Send,
+        S: AsRef<str> + Send,
+    {
+        let (headers, resources) = options.into_parts();
+        let resp = self
+            .send(
+                Request::new(
+                    RequestType::Put,
+                    object_name,
+                    Some(headers),
+                    Some(resources),
+                )
+                .body(buf),
+            )
+            .await?;
+
+        Ok(version_id(resp.headers()))
     }
 
     async fn delete_object<S>(&self, object_name: S) -> Result<(), Error>
     where
         S: AsRef<str> + Send,
     {
-        let headers = HashMap::<String, String>::new();
-        let (host, headers) =
-            self.build_request(RequestType::Delete, object_name, Some(headers), None)?;
+        self.delete_object_with_options(object_name, ObjectOptions::new())
+            .await?;
+        Ok(())
+    }
 
+    async fn delete_object_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<DeleteObjectResult, Error>
+    where
+        S: AsRef<str> + Send,
+    {
         let resp = self
-            .http_client
-            .delete(&host)
-            .headers(headers)
-            .send()
+            .send(object_request(RequestType::Delete, object_name, options))
             .await?;
 
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(Error::Object(ObjectError::DeleteError {
-                msg: format!("can not delete object, status code: {}", resp.status()).into(),
-            }))
+        Ok(DeleteObjectResult::from_header_map(resp.headers()))
+    }
+
+    async fn delete_objects<S, I>(&self, keys: I, quiet: bool) -> Result<DeleteObjectsResult, Error>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = S> + Send,
+    {
+        let keys: Vec<String> = keys.into_iter().map(|k| k.as_ref().to_string()).collect();
+        let mut result = DeleteObjectsResult::default();
+        for batch in keys.chunks(DELETE_OBJECTS_BATCH) {
+            let resp = self.send(delete_objects_request(batch, quiet)?).await?;
+            result.extend(DeleteObjectsResult::from_xml(&resp.text().await?)?);
+        }
+        Ok(result)
+    }
+
+    async fn delete_prefix<S>(&self, prefix: S) -> Result<DeletePrefixResult, Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        check_delete_prefix(prefix.as_ref())?;
+        let mut resources = HashMap::new();
+        resources.insert("prefix".to_string(), Some(prefix.as_ref().to_string()));
+
+        let mut result = DeletePrefixResult::default();
+        let mut keys = Vec::with_capacity(DELETE_OBJECTS_BATCH);
+        let mut entries = self.list_objects_v2_stream(None, Some(resources));
+        while let Some(entry) = entries.try_next().await? {
+            if let ListEntry::Object(object) = entry {
+                keys.push(object.key().to_string());
+            }
+            if keys.len() == DELETE_OBJECTS_BATCH {
+                let batch = std::mem::take(&mut keys);
+                result.add_batch(batch.len(), self.delete_objects(batch, true).await?);
+            }
+        }
+        if !keys.is_empty() {
+            result.add_batch(keys.len(), self.delete_objects(keys, true).await?);
         }
+        Ok(result)
     }
 
     async fn head_object<S>(&self, object_name: S) -> Result<ObjectMeta, Error>
     where
         S: AsRef<str> + Send,
     {
-        let (host, headers) = self.build_request(
-            RequestType::Head,
-            object_name,
-            None::<HashMap<String, String>>,
-            None,
-        )?;
-
-        let resp = self.http_client.head(&host).headers(headers).send().await?;
-
-        if resp.status().is_success() {
-            Ok(ObjectMeta::from_header_map(resp.headers())?)
-        } else {
-            Err(Error::Object(ObjectError::DeleteError {
-                msg: format!("can not head object, status code: {}", resp.status()).into(),
-            }))
-        }
+        self.head_object_with_options(object_name, ObjectOptions::new())
+            .await
+    }
+
+    async fn head_object_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<ObjectMeta, Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        let resp = self
+            .send(object_request(RequestType::Head, object_name, options))
+            .await?;
+
+        ObjectMeta::from_header_map(resp.headers())
+    }
+
+    async fn append_object<B, S1, S2, H>(
+        &self,
+        buf: B,
+        object_name: S1,
+        position: u64,
+        headers: H,
+    ) -> Result<AppendObjectResult, Error>
+    where
+        B: Into<Bytes> + Send,
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+    {
+        let resp = self
+            .send(append_object_request(buf, object_name, position, headers))
+            .await?;
+
+        AppendObjectResult::from_header_map(resp.headers())
+    }
+
+    async fn put_symlink<S1, S2>(
+        &self,
+        link: S1,
+        target: S2,
+        options: PutObjectOptions,
+    ) -> Result<Option<String>, Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+    {
+        let resp = self
+            .send(put_symlink_request(link, target, options))
+            .await?;
+
+        Ok(version_id(resp.headers()))
+    }
+
+    async fn get_symlink<S>(&self, link: S) -> Result<String, Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        let resp = self
+            .send(
+                Request::new(
+                    RequestType::Get,
+                    link,
+                    None::<HashMap<String, String>>,
+                    None,
+                )
+                .resource("symlink", None::<String>),
+            )
+            .await?;
+
+        symlink_target(resp.headers())
+    }
+
+    async fn restore_object<S>(&self, object_name: S, request: &RestoreRequest) -> Result<(), Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        self.send(restore_object_request(object_name, request)?)
+            .await?;
+        Ok(())
+    }
+
+    async fn copy_object_with_options<S1, S2>(
+        &self,
+        src: S1,
+        dest: S2,
+        options: CopyObjectOptions,
+    ) -> Result<Option<String>, Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+    {
+        let src = options.copy_source(src.as_ref());
+        let (headers, resources) = options.into_parts();
+        let resp = self
+            .send(
+                Request::new(RequestType::Put, dest, Some(headers), Some(resources))
+                    .header("x-oss-copy-source", src),
+            )
+            .await?;
+
+        Ok(version_id(resp.headers()))
+    }
+
+    async fn put_object_tagging<S>(&self, object_name: S, tagging: &Tagging) -> Result<(), Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        self.put_object_tagging_with_options(object_name, tagging, ObjectOptions::new())
+            .await
+    }
+
+    async fn get_object_tagging<S>(&self, object_name: S) -> Result<Tagging, Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        self.get_object_tagging_with_options(object_name, ObjectOptions::new())
+            .await
+    }
+
+    async fn delete_object_tagging<S>(&self, object_name: S) -> Result<(), Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        self.delete_object_tagging_with_options(object_name, ObjectOptions::new())
+            .await
+    }
+
+    async fn put_object_tagging_with_options<S>(
+        &self,
+        object_name: S,
+        tagging: &Tagging,
+        options: ObjectOptions,
+    ) -> Result<(), Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        self.send(tagging_request(RequestType::Put, object_name, options).body(tagging.to_xml()?))
+            .await?;
+        Ok(())
+    }
+
+    async fn get_object_tagging_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<Tagging, Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        let resp = self
+            .send(tagging_request(RequestType::Get, object_name, options))
+            .await?;
+
+        Tagging::from_xml(&resp.text().await?)
+    }
+
+    async fn delete_object_tagging_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<(), Error>
+    where
+        S: AsRef<str> + Send,
+    {
+        self.send(tagging_request(RequestType::Delete, object_name, options))
+            .await?;
+        Ok(())
     }
 
     async fn init_multi<S1, S2, H, R>(
@@ -322,29 +1165,45 @@ impl<'a> AsyncObjectAPI for OSS<'a> {
         H: Into<Option<HashMap<S2, S2>>> + Send,
         R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
     {
-        let (host, headers) =
-            self.build_request(RequestType::Post, object_name, headers, resources)?;
+        let resp = self
+            .send(Request::new(
+                RequestType::Post,
+                object_name,
+                headers,
+                resources,
+            ))
+            .await?;
 
-        let resp = self.http_client.post(&host).headers(headers).send().await?;
+        let body = resp.text().await?;
+        let res = quick_xml::de::from_str::<InitiateMultipartUploadResult>(&body)?;
+        Ok(res)
+    }
 
-        if resp.status().is_success() {
-            let body = resp.text().await?;
-            let res = quick_xml::de::from_str::<InitiateMultipartUploadResult>(&body)?;
-            Ok(res)
-        } else {
-            Err(Error::Object(ObjectError::PostError {
-                msg: format!(
-                    "init multi failed, status code, status code: {}",
-                    resp.status()
-                )
-                .into(),
-            }))
-        }
+    async fn upload_part<B, S1, S2, H, R>(
+        &self,
+        buf: B,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<String, Error>
+    where
+        B: Into<Bytes> + Send,
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
+    {
+        let resp = self
+            .send(Request::new(RequestType::Put, object_name, headers, resources).body(buf))
+            .await?;
+
+        upload_part_e_tag(resp.headers())
     }
 
-    async fn upload_part<S1, S2, H, R>(
+    async fn upload_part_from_reader<S1, S2, H, R, Rd>(
         &self,
-        buf: &[u8],
+        reader: Rd,
+        len: u64,
         object_name: S1,
         headers: H,
         resources: R,
@@ -354,30 +1213,68 @@ impl<'a> AsyncObjectAPI for OSS<'a> {
         S2: AsRef<str> + Send,
         H: Into<Option<HashMap<S2, S2>>> + Send,
         R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
+        Rd: AsyncRead + Send + Sync + 'static,
     {
-        let (host, headers) =
-            self.build_request(RequestType::Put, object_name, headers, resources)?;
+        self.upload_part_from_stream(
+            ReaderStream::new(reader),
+            len,
+            object_name,
+            headers,
+            resources,
+        )
+        .await
+    }
 
+    async fn upload_part_from_stream<S1, S2, H, R, St, E>(
+        &self,
+        stream: St,
+        len: u64,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<String, Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
+        St: Stream<Item = Result<Bytes, E>> + Send + Sync + 'static,
+        E: Into<Box<dyn StdError + Send + Sync>> + 'static,
+    {
         let resp = self
-            .http_client
-            .put(&host)
-            .headers(headers)
-            .body(buf.to_owned())
-            .send()
+            .send(
+                Request::new(RequestType::Put, object_name, headers, resources)
+                    .stream_body(reqwest::Body::wrap_stream(stream), len),
+            )
             .await?;
 
-        if resp.status().is_success() {
-            let e_tag = resp.headers().get("ETag").unwrap().to_str().unwrap();
-            Ok(e_tag.to_string())
-        } else {
-            Err(Error::Object(ObjectError::PutError {
-                msg: format!(
-                    "can not put object, status code, status code: {}",
-                    resp.status()
-                )
-                .into(),
-            }))
-        }
+        upload_part_e_tag(resp.headers())
+    }
+
+    async fn list_parts<S1, S2, H, R>(
+        &self,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<ListPartsResult, Error>
+    where
+        S1: AsRef<str> + Send,
+        S2: AsRef<str> + Send,
+        H: Into<Option<HashMap<S2, S2>>> + Send,
+        R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
+    {
+        let resp = self
+            .send(Request::new(
+                RequestType::Get,
+                object_name,
+                headers,
+                resources,
+            ))
+            .await?;
+
+        let body = resp.text().await?;
+        let res = quick_xml::de::from_str::<ListPartsResult>(&body)?;
+        Ok(res)
     }
 
     async fn complete_multi<S1, S2, H, R>(
@@ -393,30 +1290,13 @@ impl<'a> AsyncObjectAPI for OSS<'a> {
         H: Into<Option<HashMap<S2, S2>>> + Send,
         R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
     {
-        let (host, headers) =
-            self.build_request(RequestType::Post, object_name, headers, resources)?;
-
         let resp = self
-            .http_client
-            .post(&host)
-            .headers(headers)
-            .body(body)
-            .send()
+            .send(Request::new(RequestType::Post, object_name, headers, resources).body(body))
             .await?;
 
-        if resp.status().is_success() {
-            let body = resp.text().await?;
-            let res = quick_xml::de::from_str::<CompleteMultipartUploadResult>(&body)?;
-            Ok(res)
-        } else {
-            Err(Error::Object(ObjectError::PostError {
-                msg: format!(
-                    "complete multi failed, status code, status code: {}",
-                    resp.status()
-                )
-                .into(),
-            }))
-        }
+        let body = resp.text().await?;
+        let res = quick_xml::de::from_str::<CompleteMultipartUploadResult>(&body)?;
+        Ok(res)
     }
 
     async fn abort_multi<S1, S2, H, R>(
@@ -431,26 +1311,13 @@ impl<'a> AsyncObjectAPI for OSS<'a> {
         H: Into<Option<HashMap<S2, S2>>> + Send,
         R: Into<Option<HashMap<S2, Option<S2>>>> + Send,
     {
-        let (host, headers) =
-            self.build_request(RequestType::Delete, object_name, headers, resources)?;
-
-        let resp = self
-            .http_client
-            .delete(&host)
-            .headers(headers)
-            .send()
-            .await?;
-
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(Error::Object(ObjectError::DeleteError {
-                msg: format!(
-                    "abort multi failed, status code, status code: {}",
-                    resp.status()
-                )
-                .into(),
-            }))
-        }
+        self.send(Request::new(
+            RequestType::Delete,
+            object_name,
+            headers,
+            resources,
+        ))
+        .await?;
+        Ok(())
     }
 }
@@ -1,12 +1,11 @@
 use async_trait::async_trait;
-use reqwest::header::{HeaderMap, DATE};
 use std::collections::HashMap;
 
 use crate::prelude::ListBuckets;
 
-use super::auth::*;
 use super::errors::Error;
-use super::oss::OSS;
+use super::oss::{RequestType, OSS};
+use super::request::Request;
 
 #[derive(Clone, Debug)]
 pub struct Bucket {
@@ -77,28 +76,9 @@ impl<'a> ServiceAPI for OSS<'a> {
         S: AsRef<str> + Send,
         R: Into<Option<HashMap<S, Option<S>>>> + Send,
     {
-        let resources_str = if let Some(r) = resources.into() {
-            self.get_resources_str(&r)
-        } else {
-            String::new()
-        };
-        let host = self.endpoint();
-        let date = self.date();
-
-        let mut headers = HeaderMap::new();
-        headers.insert(DATE, date.parse()?);
-        let authorization = self.oss_sign(
-            "GET",
-            self.key_id(),
-            self.key_secret(),
-            "",
-            "",
-            &resources_str,
-            &headers,
-        );
-        headers.insert("Authorization", authorization.parse()?);
-
-        let resp = self.http_client.get(host).headers(headers).send().await?;
+        let resp = self
+            .send(Request::service(RequestType::Get, resources))
+            .await?;
 
         let body = resp.text().await?;
         let list_buckets = quick_xml::de::from_str::<ListBuckets>(&body)?;
@@ -0,0 +1,469 @@
+//! Creation, deletion and description of the bucket of a client.
+//!
+//! Every method applies to [`OSS::bucket`], use `set_bucket` to manage
+//! another one.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::cors::CorsConfiguration;
+use crate::errors::Error;
+use crate::lifecycle::LifecycleConfiguration;
+use crate::object::Owner;
+use crate::options::{Headers, StorageClass};
+use crate::oss::{RequestType, OSS};
+use crate::policy::PolicyDocument;
+use crate::request::Request;
+use crate::utils::content_md5;
+use crate::versioning::VersioningConfiguration;
+
+/// Canned ACL of a bucket, sent in `x-oss-acl`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BucketAcl {
+    Private,
+    PublicRead,
+    PublicReadWrite,
+}
+
+impl BucketAcl {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BucketAcl::Private => "private",
+            BucketAcl::PublicRead => "public-read",
+            BucketAcl::PublicReadWrite => "public-read-write",
+        }
+    }
+}
+
+impl fmt::Display for BucketAcl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// How the data of a bucket is replicated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataRedundancyType {
+    /// Locally redundant storage, in a single zone.
+    LRS,
+    /// Zone-redundant storage, across the zones of the region.
+    ZRS,
+}
+
+impl DataRedundancyType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DataRedundancyType::LRS => "LRS",
+            DataRedundancyType::ZRS => "ZRS",
+        }
+    }
+}
+
+impl fmt::Display for DataRedundancyType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Body of PutBucket.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CreateBucketConfiguration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub storage_class: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_redundancy_type: Option<String>,
+}
+
+/// Options of PutBucket.
+///
+/// # Examples
+///
+/// ```
+/// use oss_rust_sdk::bucket::{BucketAcl, DataRedundancyType, PutBucketOptions};
+/// use oss_rust_sdk::options::StorageClass;
+///
+/// let options = PutBucketOptions::new()
+///     .acl(BucketAcl::Private)
+///     .storage_class(StorageClass::IA)
+///     .data_redundancy_type(DataRedundancyType::ZRS);
+/// assert_eq!(
+///     options.to_xml().unwrap(),
+///     "<CreateBucketConfiguration><StorageClass>IA</StorageClass>\
+///      <DataRedundancyType>ZRS</DataRedundancyType></CreateBucketConfiguration>"
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PutBucketOptions {
+    headers: Headers,
+    configuration: CreateBucketConfiguration,
+}
+
+impl PutBucketOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn acl(self, acl: BucketAcl) -> Self {
+        self.header("x-oss-acl", acl.as_str())
+    }
+
+    /// Default storage class of the objects, `Standard` when not set.
+    pub fn storage_class(mut self, storage_class: StorageClass) -> Self {
+        self.configuration.storage_class = Some(storage_class.as_str().to_string());
+        self
+    }
+
+    /// `LRS` when not set.
+    pub fn data_redundancy_type(mut self, data_redundancy_type: DataRedundancyType) -> Self {
+        self.configuration.data_redundancy_type = Some(data_redundancy_type.as_str().to_string());
+        self
+    }
+
+    pub fn resource_group_id<S: Into<String>>(self, id: S) -> Self {
+        self.header("x-oss-resource-group-id", id)
+    }
+
+    /// Any other header.
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn to_xml(&self) -> Result<String, Error> {
+        Ok(quick_xml::se::to_string_with_root(
+            "CreateBucketConfiguration",
+            &self.configuration,
+        )?)
+    }
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AccessControlList {
+    #[serde(default)]
+    pub grant: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ServerSideEncryptionRule {
+    #[serde(default, rename = "SSEAlgorithm")]
+    pub sse_algorithm: String,
+    #[serde(default, rename = "KMSMasterKeyID")]
+    pub kms_master_key_id: String,
+    #[serde(default, rename = "KMSDataEncryption")]
+    pub kms_data_encryption: String,
+}
+
+/// Description of a bucket, as returned by GetBucketInfo.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BucketInfo {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub location: String,
+    #[serde(default)]
+    pub creation_date: String,
+    #[serde(default)]
+    pub extranet_endpoint: String,
+    #[serde(default)]
+    pub intranet_endpoint: String,
+    #[serde(default)]
+    pub storage_class: String,
+    #[serde(default)]
+    pub data_redundancy_type: String,
+    #[serde(default)]
+    pub access_monitor: String,
+    #[serde(default)]
+    pub transfer_acceleration: String,
+    #[serde(default)]
+    pub cross_region_replication: String,
+    /// `Enabled` or `Suspended`, empty when versioning was never enabled.
+    #[serde(default)]
+    pub versioning: String,
+    #[serde(default)]
+    pub resource_group_id: String,
+    #[serde(default)]
+    pub comment: String,
+    #[serde(default)]
+    pub owner: Owner,
+    #[serde(default)]
+    pub access_control_list: AccessControlList,
+    #[serde(default)]
+    pub server_side_encryption_rule: ServerSideEncryptionRule,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct BucketInfoResult {
+    bucket: BucketInfo,
+}
+
+impl BucketInfo {
+    /// Parse a GetBucketInfo response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oss_rust_sdk::bucket::BucketInfo;
+    ///
+    /// let info = BucketInfo::from_xml(
+    ///     "<BucketInfo><Bucket><Name>demo</Name><Location>oss-cn-hangzhou</Location>\
+    ///      <StorageClass>Standard</StorageClass><DataRedundancyType>LRS</DataRedundancyType>\
+    ///      <Owner><DisplayName>1234</DisplayName><ID>1234</ID></Owner>\
+    ///      <AccessControlList><Grant>private</Grant></AccessControlList></Bucket></BucketInfo>",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(info.name, "demo");
+    /// assert_eq!(info.access_control_list.grant, "private");
+    /// ```
+    pub fn from_xml(body: &str) -> Result<Self, Error> {
+        Ok(quick_xml::de::from_str::<BucketInfoResult>(body)?.bucket)
+    }
+}
+
+#[derive(Deserialize)]
+struct LocationConstraint {
+    #[serde(rename = "$value", default)]
+    location: String,
+}
+
+/// Region of a bucket from a GetBucketLocation response, e.g. `oss-cn-hangzhou`.
+///
+/// # Examples
+///
+/// ```
+/// use oss_rust_sdk::bucket::parse_bucket_location;
+///
+/// let body = "<LocationConstraint>oss-cn-hangzhou</LocationConstraint>";
+/// assert_eq!(parse_bucket_location(body).unwrap(), "oss-cn-hangzhou");
+/// ```
+pub fn parse_bucket_location(body: &str) -> Result<String, Error> {
+    Ok(quick_xml::de::from_str::<LocationConstraint>(body)?.location)
+}
+
+/// Storage used by a bucket, as returned by GetBucketStat. Sizes are in
+/// bytes, and the statistics are updated about every hour.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BucketStat {
+    #[serde(default)]
+    pub storage: u64,
+    #[serde(default)]
+    pub object_count: u64,
+    #[serde(default)]
+    pub multipart_upload_count: u64,
+    #[serde(default)]
+    pub live_channel_count: u64,
+    /// Unix time of the statistics.
+    #[serde(default)]
+    pub last_modified_time: u64,
+    #[serde(default)]
+    pub standard_storage: u64,
+    #[serde(default)]
+    pub standard_object_count: u64,
+    #[serde(default)]
+    pub infrequent_access_storage: u64,
+    #[serde(default)]
+    pub infrequent_access_real_storage: u64,
+    #[serde(default)]
+    pub infrequent_access_object_count: u64,
+    #[serde(default)]
+    pub archive_storage: u64,
+    #[serde(default)]
+    pub archive_real_storage: u64,
+    #[serde(default)]
+    pub archive_object_count: u64,
+    #[serde(default)]
+    pub cold_archive_storage: u64,
+    #[serde(default)]
+    pub cold_archive_real_storage: u64,
+    #[serde(default)]
+    pub cold_archive_object_count: u64,
+    #[serde(default)]
+    pub deep_cold_archive_storage: u64,
+    #[serde(default)]
+    pub deep_cold_archive_real_storage: u64,
+    #[serde(default)]
+    pub deep_cold_archive_object_count: u64,
+}
+
+impl BucketStat {
+    /// Parse a GetBucketStat response.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oss_rust_sdk::bucket::BucketStat;
+    ///
+    /// let stat = BucketStat::from_xml(
+    ///     "<BucketStat><Storage>1600</Storage><ObjectCount>230</ObjectCount>\
+    ///      <MultipartUploadCount>40</MultipartUploadCount></BucketStat>",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(stat.storage, 1600);
+    /// assert_eq!(stat.object_count, 230);
+    /// ```
+    pub fn from_xml(body: &str) -> Result<Self, Error> {
+        Ok(quick_xml::de::from_str(body)?)
+    }
+}
+
+/// A request on the bucket itself, with the subresource `resource`.
+pub(crate) fn bucket_request(req_type: RequestType, resource: Option<&str>) -> Request {
+    let request = Request::new(req_type, "", None::<HashMap<String, String>>, None);
+    match resource {
+        Some(resource) => request.resource(resource, None::<String>),
+        None => request,
+    }
+}
+
+/// A request setting the `resource` configuration of the bucket to `body`.
+pub(crate) fn put_bucket_config_request(resource: &str, body: String) -> Request {
+    bucket_request(RequestType::Put, Some(resource))
+        .header("Content-MD5", content_md5(body.as_bytes()))
+        .body(body)
+}
+
+pub(crate) fn put_bucket_request(options: PutBucketOptions) -> Result<Request, Error> {
+    let body = options.to_xml()?;
+    Ok(Request::new(
+        RequestType::Put,
+        "",
+        Some(options.headers),
+        None::<HashMap<String, Option<String>>>,
+    )
+    .body(body))
+}
+
+pub trait BucketAPI {
+    /// Create the bucket of the client.
+    fn put_bucket(&self, options: PutBucketOptions) -> Result<(), Error>;
+
+    /// Delete the bucket of the client, which must be empty.
+    fn delete_bucket(&self) -> Result<(), Error>;
+
+    fn get_bucket_info(&self) -> Result<BucketInfo, Error>;
+
+    /// Region of the bucket, e.g. `oss-cn-hangzhou`.
+    fn get_bucket_location(&self) -> Result<String, Error>;
+
+    fn get_bucket_stat(&self) -> Result<BucketStat, Error>;
+
+    /// Replace the lifecycle rules of the bucket.
+    fn put_bucket_lifecycle(&self, config: &LifecycleConfiguration) -> Result<(), Error>;
+
+    fn get_bucket_lifecycle(&self) -> Result<LifecycleConfiguration, Error>;
+
+    fn delete_bucket_lifecycle(&self) -> Result<(), Error>;
+
+    /// Replace the CORS rules of the bucket.
+    fn put_bucket_cors(&self, config: &CorsConfiguration) -> Result<(), Error>;
+
+    fn get_bucket_cors(&self) -> Result<CorsConfiguration, Error>;
+
+    fn delete_bucket_cors(&self) -> Result<(), Error>;
+
+    /// Replace the policy of the bucket.
+    fn put_bucket_policy(&self, policy: &PolicyDocument) -> Result<(), Error>;
+
+    fn get_bucket_policy(&self) -> Result<PolicyDocument, Error>;
+
+    fn delete_bucket_policy(&self) -> Result<(), Error>;
+
+    /// Enable or suspend versioning of the bucket.
+    fn put_bucket_versioning(&self, config: &VersioningConfiguration) -> Result<(), Error>;
+
+    fn get_bucket_versioning(&self) -> Result<VersioningConfiguration, Error>;
+}
+
+impl<'a> BucketAPI for OSS<'a> {
+    fn put_bucket(&self, options: PutBucketOptions) -> Result<(), Error> {
+        self.send_blocking(put_bucket_request(options)?)?;
+        Ok(())
+    }
+
+    fn delete_bucket(&self) -> Result<(), Error> {
+        self.send_blocking(bucket_request(RequestType::Delete, None))?;
+        Ok(())
+    }
+
+    fn get_bucket_info(&self) -> Result<BucketInfo, Error> {
+        let resp = self.send_blocking(bucket_request(RequestType::Get, Some("bucketInfo")))?;
+
+        BucketInfo::from_xml(&resp.text()?)
+    }
+
+    fn get_bucket_location(&self) -> Result<String, Error> {
+        let resp = self.send_blocking(bucket_request(RequestType::Get, Some("location")))?;
+
+        parse_bucket_location(&resp.text()?)
+    }
+
+    fn get_bucket_stat(&self) -> Result<BucketStat, Error> {
+        let resp = self.send_blocking(bucket_request(RequestType::Get, Some("stat")))?;
+
+        BucketStat::from_xml(&resp.text()?)
+    }
+
+    fn put_bucket_lifecycle(&self, config: &LifecycleConfiguration) -> Result<(), Error> {
+        self.send_blocking(put_bucket_config_request("lifecycle", config.to_xml()?))?;
+        Ok(())
+    }
+
+    fn get_bucket_lifecycle(&self) -> Result<LifecycleConfiguration, Error> {
+        let resp = self.send_blocking(bucket_request(RequestType::Get, Some("lifecycle")))?;
+
+        LifecycleConfiguration::from_xml(&resp.text()?)
+    }
+
+    fn delete_bucket_lifecycle(&self) -> Result<(), Error> {
+        self.send_blocking(bucket_request(RequestType::Delete, Some("lifecycle")))?;
+        Ok(())
+    }
+
+    fn put_bucket_cors(&self, config: &CorsConfiguration) -> Result<(), Error> {
+        self.send_blocking(put_bucket_config_request("cors", config.to_xml()?))?;
+        Ok(())
+    }
+
+    fn get_bucket_cors(&self) -> Result<CorsConfiguration, Error> {
+        let resp = self.send_blocking(bucket_request(RequestType::Get, Some("cors")))?;
+
+        CorsConfiguration::from_xml(&resp.text()?)
+    }
+
+    fn delete_bucket_cors(&self) -> Result<(), Error> {
+        self.send_blocking(bucket_request(RequestType::Delete, Some("cors")))?;
+        Ok(())
+    }
+
+    fn put_bucket_policy(&self, policy: &PolicyDocument) -> Result<(), Error> {
+        self.send_blocking(put_bucket_config_request("policy", policy.to_json()?))?;
+        Ok(())
+    }
+
+    fn get_bucket_policy(&self) -> Result<PolicyDocument, Error> {
+        let resp = self.send_blocking(bucket_request(RequestType::Get, Some("policy")))?;
+
+        PolicyDocument::from_json(&resp.text()?)
+    }
+
+    fn delete_bucket_policy(&self) -> Result<(), Error> {
+        self.send_blocking(bucket_request(RequestType::Delete, Some("policy")))?;
+        Ok(())
+    }
+
+    fn put_bucket_versioning(&self, config: &VersioningConfiguration) -> Result<(), Error> {
+        self.send_blocking(put_bucket_config_request("versioning", config.to_xml()?))?;
+        Ok(())
+    }
+
+    fn get_bucket_versioning(&self) -> Result<VersioningConfiguration, Error> {
+        let resp = self.send_blocking(bucket_request(RequestType::Get, Some("versioning")))?;
+
+        VersioningConfiguration::from_xml(&resp.text()?)
+    }
+}
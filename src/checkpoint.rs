@@ -0,0 +1,165 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use super::errors::Error;
+use super::multi_part::Part;
+
+/// Size and modification time of a local file, to detect that it changed
+/// since a checkpoint was saved.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FileFingerprint {
+    pub size: u64,
+    pub mtime_secs: u64,
+    pub mtime_nanos: u32,
+}
+
+impl FileFingerprint {
+    pub async fn of<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let metadata = tokio::fs::metadata(path).await?;
+        let mtime = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Ok(FileFingerprint {
+            size: metadata.len(),
+            mtime_secs: mtime.as_secs(),
+            mtime_nanos: mtime.subsec_nanos(),
+        })
+    }
+}
+
+/// Progress of a resumable upload, saved as JSON after every part.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct UploadCheckpoint {
+    pub bucket: String,
+    pub object_name: String,
+    pub file: FileFingerprint,
+    pub upload_id: String,
+    pub part_size: u64,
+    /// Uploaded parts, in the order they completed.
+    pub parts: Vec<Part>,
+}
+
+impl UploadCheckpoint {
+    /// Whether the checkpoint was saved for the same upload of the same file.
+    pub fn matches(&self, bucket: &str, object_name: &str, file: &FileFingerprint) -> bool {
+        self.bucket == bucket && self.object_name == object_name && self.file == *file
+    }
+}
+
+/// Progress of a resumable download, saved as JSON after every range.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct DownloadCheckpoint {
+    pub bucket: String,
+    pub object_name: String,
+    /// ETag of the object when the download started.
+    pub e_tag: String,
+    pub size: u64,
+    pub part_size: u64,
+    /// Downloaded ranges, in the order they completed.
+    pub parts: Vec<DownloadedPart>,
+}
+
+/// A range written to the temporary file, `part_number` from 1.
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DownloadedPart {
+    pub part_number: usize,
+    pub crc64: u64,
+}
+
+/// Read a checkpoint, `None` when there is none or it can not be parsed.
+pub(crate) async fn load<T: DeserializeOwned>(path: &Path) -> Result<Option<T>, Error> {
+    let buf = match tokio::fs::read(path).await {
+        Ok(buf) => buf,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+    match serde_json::from_slice(&buf) {
+        Ok(checkpoint) => Ok(Some(checkpoint)),
+        Err(e) => {
+            warn!("ignore invalid checkpoint {}: {}", path.display(), e);
+            Ok(None)
+        }
+    }
+}
+
+/// Write a checkpoint through a temporary file, so that a crash never leaves
+/// a truncated one behind.
+pub(crate) async fn save<T: Serialize>(path: &Path, checkpoint: &T) -> Result<(), Error> {
+    let buf = serde_json::to_vec(checkpoint).map_err(io::Error::from)?;
+    let mut tmp = PathBuf::from(path);
+    tmp.as_mut_os_string().push(".tmp");
+    tokio::fs::write(&tmp, buf).await?;
+    tokio::fs::rename(&tmp, path).await?;
+    Ok(())
+}
+
+pub(crate) async fn remove(path: &Path) -> Result<(), Error> {
+    match tokio::fs::remove_file(path).await {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "oss-rust-sdk-checkpoint-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[tokio::test]
+    async fn save_renames_the_tmp_file_over_the_checkpoint() {
+        let dir = dir("save");
+        let path = dir.join("object.checkpoint");
+        let tmp = dir.join("object.checkpoint.tmp");
+        let mut checkpoint = DownloadCheckpoint {
+            bucket: "bucket".to_string(),
+            object_name: "object".to_string(),
+            e_tag: "\"5B3C1A2E053D763E1B002CC607C5A0FE\"".to_string(),
+            size: 1000,
+            part_size: 300,
+            parts: Vec::new(),
+        };
+        save(&path, &checkpoint).await.unwrap();
+        #[cfg(unix)]
+        let inode = std::os::unix::fs::MetadataExt::ino(&std::fs::metadata(&path).unwrap());
+
+        // a tmp file left by a crash is replaced
+        std::fs::write(&tmp, "{\"bucket\":").unwrap();
+        checkpoint.parts.push(DownloadedPart {
+            part_number: 1,
+            crc64: 42,
+        });
+        save(&path, &checkpoint).await.unwrap();
+
+        assert!(!tmp.exists());
+        assert_eq!(load(&path).await.unwrap(), Some(checkpoint));
+        // a new file, the old one was never written in place
+        #[cfg(unix)]
+        assert_ne!(
+            std::os::unix::fs::MetadataExt::ino(&std::fs::metadata(&path).unwrap()),
+            inode
+        );
+    }
+
+    #[tokio::test]
+    async fn missing_or_invalid_checkpoint_is_none() {
+        let dir = dir("load");
+        let path = dir.join("object.checkpoint");
+        assert_eq!(load::<DownloadCheckpoint>(&path).await.unwrap(), None);
+        std::fs::write(&path, "{\"bucket\":").unwrap();
+        assert_eq!(load::<DownloadCheckpoint>(&path).await.unwrap(), None);
+    }
+}
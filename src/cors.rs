@@ -0,0 +1,155 @@
+//! Cross-origin resource sharing rules of a bucket.
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::Error;
+
+/// Body of PutBucketCors and GetBucketCors.
+///
+/// # Examples
+///
+/// ```
+/// use oss_rust_sdk::cors::{CorsConfiguration, CorsRule};
+///
+/// let config = CorsConfiguration::new(vec![CorsRule::new()
+///     .allowed_origin("https://*.example.com")
+///     .allowed_method("PUT")
+///     .allowed_method("POST")
+///     .allowed_header("content-*")
+///     .allowed_header("x-oss-*")
+///     .expose_header("ETag")
+///     .max_age_seconds(600)]);
+///
+/// let xml = config.to_xml().unwrap();
+/// assert_eq!(CorsConfiguration::from_xml(&xml).unwrap(), config);
+///
+/// assert!(config
+///     .allowed_rule("https://app.example.com", "PUT", &["Content-Type", "x-oss-meta-a"])
+///     .is_some());
+/// assert!(config.allowed_rule("https://evil.com", "PUT", &[]).is_none());
+/// assert!(config.allowed_rule("https://app.example.com", "DELETE", &[]).is_none());
+/// assert!(config
+///     .allowed_rule("https://app.example.com", "PUT", &["Authorization"])
+///     .is_none());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CorsConfiguration {
+    #[serde(rename = "CORSRule", default)]
+    pub rules: Vec<CorsRule>,
+    /// Send `Vary: Origin` with the responses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_vary: Option<bool>,
+}
+
+impl CorsConfiguration {
+    pub fn new(rules: Vec<CorsRule>) -> Self {
+        CorsConfiguration {
+            rules,
+            response_vary: None,
+        }
+    }
+
+    pub fn response_vary(mut self, response_vary: bool) -> Self {
+        self.response_vary = Some(response_vary);
+        self
+    }
+
+    /// The first rule allowing a preflight request from `origin` for `method`
+    /// with the `headers` of `Access-Control-Request-Headers`, as OSS
+    /// evaluates it.
+    pub fn allowed_rule(&self, origin: &str, method: &str, headers: &[&str]) -> Option<&CorsRule> {
+        self.rules
+            .iter()
+            .find(|rule| rule.allows(origin, method, headers))
+    }
+
+    pub fn to_xml(&self) -> Result<String, Error> {
+        Ok(quick_xml::se::to_string_with_root(
+            "CORSConfiguration",
+            self,
+        )?)
+    }
+
+    pub fn from_xml(body: &str) -> Result<Self, Error> {
+        Ok(quick_xml::de::from_str(body)?)
+    }
+}
+
+/// A CORS rule. Origins and headers can contain one `*` wildcard.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CorsRule {
+    #[serde(default)]
+    pub allowed_origin: Vec<String>,
+    /// `GET`, `PUT`, `DELETE`, `POST` or `HEAD`.
+    #[serde(default)]
+    pub allowed_method: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_header: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expose_header: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_age_seconds: Option<u32>,
+}
+
+impl CorsRule {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn allowed_origin<S: Into<String>>(mut self, origin: S) -> Self {
+        self.allowed_origin.push(origin.into());
+        self
+    }
+
+    pub fn allowed_method<S: Into<String>>(mut self, method: S) -> Self {
+        self.allowed_method.push(method.into());
+        self
+    }
+
+    pub fn allowed_header<S: Into<String>>(mut self, header: S) -> Self {
+        self.allowed_header.push(header.into());
+        self
+    }
+
+    pub fn expose_header<S: Into<String>>(mut self, header: S) -> Self {
+        self.expose_header.push(header.into());
+        self
+    }
+
+    pub fn max_age_seconds(mut self, seconds: u32) -> Self {
+        self.max_age_seconds = Some(seconds);
+        self
+    }
+
+    /// Whether the rule allows `origin` to send `method` with `headers`.
+    pub fn allows(&self, origin: &str, method: &str, headers: &[&str]) -> bool {
+        self.allowed_origin
+            .iter()
+            .any(|o| wildcard_match(o, origin, false))
+            && self.allowed_method.iter().any(|m| m == method)
+            && headers.iter().all(|h| {
+                self.allowed_header
+                    .iter()
+                    .any(|a| wildcard_match(a, h.trim(), true))
+            })
+    }
+}
+
+/// Match `value` against `pattern`, which can contain one `*`.
+fn wildcard_match(pattern: &str, value: &str, ignore_case: bool) -> bool {
+    let (pattern, value) = if ignore_case {
+        (pattern.to_ascii_lowercase(), value.to_ascii_lowercase())
+    } else {
+        (pattern.to_string(), value.to_string())
+    };
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => {
+            value.len() >= prefix.len() + suffix.len()
+                && value.starts_with(prefix)
+                && value.ends_with(suffix)
+        }
+        None => pattern == value,
+    }
+}
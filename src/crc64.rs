@@ -0,0 +1,153 @@
+//! CRC-64/ECMA-182 as computed by OSS and returned in `x-oss-hash-crc64ecma`.
+
+/// Reflected ECMA-182 polynomial.
+const POLY: u64 = 0xC96C_5795_D787_0F42;
+
+const TABLE: [u64; 256] = make_table();
+
+const fn make_table() -> [u64; 256] {
+    let mut table = [0u64; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u64;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ POLY
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// Incremental CRC64 of a byte sequence.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Crc64 {
+    crc: u64,
+}
+
+impl Crc64 {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Continue from the CRC64 of the data seen so far, e.g. the current
+    /// CRC64 of an appendable object.
+    pub fn with_initial(crc: u64) -> Self {
+        Crc64 { crc }
+    }
+
+    pub fn update(&mut self, buf: &[u8]) {
+        let mut crc = !self.crc;
+        for b in buf {
+            crc = TABLE[((crc ^ *b as u64) & 0xff) as usize] ^ (crc >> 8);
+        }
+        self.crc = !crc;
+    }
+
+    pub fn value(&self) -> u64 {
+        self.crc
+    }
+}
+
+/// CRC64 of the sequence of data with CRC64 `crc1`, followed by `len2` bytes
+/// of data with CRC64 `crc2`, without reading the data again.
+pub fn combine(mut crc1: u64, crc2: u64, mut len2: u64) -> u64 {
+    if len2 == 0 {
+        return crc1;
+    }
+
+    // operator for one zero bit in `odd`, then two and four zero bits
+    let mut odd = [0u64; 64];
+    let mut even = [0u64; 64];
+    odd[0] = POLY;
+    for (n, row) in odd.iter_mut().enumerate().skip(1) {
+        *row = 1 << (n - 1);
+    }
+    gf2_matrix_square(&mut even, &odd);
+    gf2_matrix_square(&mut odd, &even);
+
+    // apply `len2` zero bytes to `crc1`
+    loop {
+        gf2_matrix_square(&mut even, &odd);
+        if len2 & 1 == 1 {
+            crc1 = gf2_matrix_times(&even, crc1);
+        }
+        len2 >>= 1;
+        if len2 == 0 {
+            break;
+        }
+
+        gf2_matrix_square(&mut odd, &even);
+        if len2 & 1 == 1 {
+            crc1 = gf2_matrix_times(&odd, crc1);
+        }
+        len2 >>= 1;
+        if len2 == 0 {
+            break;
+        }
+    }
+    crc1 ^ crc2
+}
+
+fn gf2_matrix_times(mat: &[u64; 64], mut vec: u64) -> u64 {
+    let mut sum = 0;
+    let mut i = 0;
+    while vec != 0 {
+        if vec & 1 == 1 {
+            sum ^= mat[i];
+        }
+        vec >>= 1;
+        i += 1;
+    }
+    sum
+}
+
+fn gf2_matrix_square(square: &mut [u64; 64], mat: &[u64; 64]) {
+    for (n, row) in square.iter_mut().enumerate() {
+        *row = gf2_matrix_times(mat, mat[n]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crc(buf: &[u8]) -> u64 {
+        let mut crc = Crc64::new();
+        crc.update(buf);
+        crc.value()
+    }
+
+    #[test]
+    fn check_value() {
+        assert_eq!(crc(b"123456789"), 0x995D_C9BB_DF19_39FA);
+        assert_eq!(crc(b""), 0);
+    }
+
+    #[test]
+    fn update_continues_from_initial() {
+        let mut crc64 = Crc64::with_initial(crc(b"1234"));
+        crc64.update(b"56789");
+        assert_eq!(crc64.value(), crc(b"123456789"));
+    }
+
+    #[test]
+    fn combine_is_the_crc_of_the_concatenation() {
+        let data: Vec<u8> = (0..10_000u32).map(|i| (i * 31 % 251) as u8).collect();
+        for split in [0, 1, 7, 4096, 9_999, 10_000] {
+            let (a, b) = data.split_at(split);
+            assert_eq!(
+                combine(crc(a), crc(b), b.len() as u64),
+                crc(&data),
+                "split at {}",
+                split
+            );
+        }
+    }
+}
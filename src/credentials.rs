@@ -0,0 +1,985 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration as StdDuration;
+use std::{env, fs, thread};
+
+use super::errors::{CredentialsError, Error};
+
+const ECS_METADATA_ENDPOINT: &str =
+    "http://100.100.100.200/latest/meta-data/ram/security-credentials/";
+const STS_ENDPOINT: &str = "https://sts.aliyuncs.com";
+/// The metadata service is local to the instance, it answers fast or never.
+const ECS_CONNECT_TIMEOUT: StdDuration = StdDuration::from_secs(1);
+const ECS_TIMEOUT: StdDuration = StdDuration::from_secs(5);
+const STS_CONNECT_TIMEOUT: StdDuration = StdDuration::from_secs(5);
+const STS_TIMEOUT: StdDuration = StdDuration::from_secs(10);
+
+/// An AccessKey pair, optionally with the STS token and expiration of
+/// temporary credentials.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    access_key_id: String,
+    access_key_secret: String,
+    security_token: Option<String>,
+    expiration: Option<DateTime<Utc>>,
+}
+
+impl Credentials {
+    pub fn new<S>(access_key_id: S, access_key_secret: S) -> Self
+    where
+        S: Into<String>,
+    {
+        Credentials {
+            access_key_id: access_key_id.into(),
+            access_key_secret: access_key_secret.into(),
+            security_token: None,
+            expiration: None,
+        }
+    }
+
+    pub fn with_security_token<S>(mut self, security_token: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.security_token = Some(security_token.into());
+        self
+    }
+
+    pub fn with_expiration(mut self, expiration: DateTime<Utc>) -> Self {
+        self.expiration = Some(expiration);
+        self
+    }
+
+    pub fn access_key_id(&self) -> &str {
+        &self.access_key_id
+    }
+
+    pub fn access_key_secret(&self) -> &str {
+        &self.access_key_secret
+    }
+
+    pub fn security_token(&self) -> Option<&str> {
+        self.security_token.as_deref()
+    }
+
+    pub fn expiration(&self) -> Option<DateTime<Utc>> {
+        self.expiration
+    }
+
+    /// Whether the credentials expire within `duration` from now. Credentials
+    /// without an expiration never expire.
+    pub fn expires_within(&self, duration: Duration) -> bool {
+        self.expiration
+            .map(|expiration| expiration - duration <= Utc::now())
+            .unwrap_or(false)
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("access_key_secret", &"******")
+            .field(
+                "security_token",
+                &self.security_token.as_ref().map(|_| "******"),
+            )
+            .field("expiration", &self.expiration)
+            .finish()
+    }
+}
+
+/// Source of the credentials used to sign every request.
+///
+/// `credentials` is called once per request, providers fetching remote
+/// credentials should be wrapped in a [`CachedProvider`].
+#[async_trait]
+pub trait CredentialsProvider: fmt::Debug + Send + Sync {
+    fn credentials(&self) -> Result<Credentials, Error>;
+
+    /// Called by the async API instead of `credentials`. The default calls
+    /// `credentials`, providers doing network io override it so they do not
+    /// block the runtime.
+    async fn credentials_async(&self) -> Result<Credentials, Error> {
+        self.credentials()
+    }
+}
+
+/// Fixed credentials.
+#[derive(Clone, Debug)]
+pub struct StaticProvider {
+    credentials: Credentials,
+}
+
+impl StaticProvider {
+    pub fn new(credentials: Credentials) -> Self {
+        StaticProvider { credentials }
+    }
+}
+
+impl CredentialsProvider for StaticProvider {
+    fn credentials(&self) -> Result<Credentials, Error> {
+        Ok(self.credentials.clone())
+    }
+}
+
+/// Reads `OSS_ACCESS_KEY_ID`, `OSS_ACCESS_KEY_SECRET` and `OSS_SESSION_TOKEN`,
+/// falling back to the `ALIBABA_CLOUD_ACCESS_KEY_ID`, `ALIBABA_CLOUD_ACCESS_KEY_SECRET`
+/// and `ALIBABA_CLOUD_SECURITY_TOKEN` variables.
+#[derive(Clone, Debug, Default)]
+pub struct EnvironmentProvider;
+
+impl EnvironmentProvider {
+    pub fn new() -> Self {
+        EnvironmentProvider
+    }
+}
+
+impl CredentialsProvider for EnvironmentProvider {
+    fn credentials(&self) -> Result<Credentials, Error> {
+        let var = |names: &[&str]| names.iter().find_map(|name| env::var(name).ok());
+        let access_key_id = var(&["OSS_ACCESS_KEY_ID", "ALIBABA_CLOUD_ACCESS_KEY_ID"]);
+        let access_key_secret = var(&["OSS_ACCESS_KEY_SECRET", "ALIBABA_CLOUD_ACCESS_KEY_SECRET"]);
+        let (access_key_id, access_key_secret) = match (access_key_id, access_key_secret) {
+            (Some(id), Some(secret)) => (id, secret),
+            _ => {
+                return Err(Error::Credentials(CredentialsError::Missing {
+                    msg: "access key id or secret is not set in the environment".to_string(),
+                }))
+            }
+        };
+
+        let mut credentials = Credentials::new(access_key_id, access_key_secret);
+        if let Some(token) = var(&["OSS_SESSION_TOKEN", "ALIBABA_CLOUD_SECURITY_TOKEN"]) {
+            credentials = credentials.with_security_token(token);
+        }
+        Ok(credentials)
+    }
+}
+
+/// Reads a section of an ossutil style config file:
+///
+/// ```text
+/// [Credentials]
+/// endpoint=oss-cn-hangzhou.aliyuncs.com
+/// accessKeyID=your_AccessKeyId
+/// accessKeySecret=your_AccessKeySecret
+/// stsToken=your_SecurityToken
+/// ```
+#[derive(Clone, Debug)]
+pub struct ProfileProvider {
+    path: PathBuf,
+    profile: String,
+}
+
+impl ProfileProvider {
+    pub fn new<P, S>(path: P, profile: S) -> Self
+    where
+        P: Into<PathBuf>,
+        S: Into<String>,
+    {
+        ProfileProvider {
+            path: path.into(),
+            profile: profile.into(),
+        }
+    }
+
+    fn load_profile(&self) -> Result<HashMap<String, String>, Error> {
+        let content = fs::read_to_string(&self.path)?;
+        let mut in_profile = false;
+        let mut values = HashMap::new();
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                let section = line[1..line.len() - 1].trim();
+                in_profile = section == self.profile
+                    || section.strip_prefix("profile ").map(str::trim) == Some(&self.profile);
+                continue;
+            }
+            if let (true, Some((key, value))) = (in_profile, line.split_once('=')) {
+                values.insert(key.trim().to_lowercase(), value.trim().to_string());
+            }
+        }
+        Ok(values)
+    }
+}
+
+impl Default for ProfileProvider {
+    /// `~/.ossutilconfig`, section `[Credentials]`.
+    fn default() -> Self {
+        let home = env::var("HOME")
+            .or_else(|_| env::var("USERPROFILE"))
+            .unwrap_or_default();
+        ProfileProvider::new(PathBuf::from(home).join(".ossutilconfig"), "Credentials")
+    }
+}
+
+impl CredentialsProvider for ProfileProvider {
+    fn credentials(&self) -> Result<Credentials, Error> {
+        let mut values = self.load_profile()?;
+        let (access_key_id, access_key_secret) = match (
+            values.remove("accesskeyid"),
+            values.remove("accesskeysecret"),
+        ) {
+            (Some(id), Some(secret)) => (id, secret),
+            _ => {
+                return Err(Error::Credentials(CredentialsError::Missing {
+                    msg: format!(
+                        "access key id or secret is not set in profile {} of {}",
+                        self.profile,
+                        self.path.display()
+                    ),
+                }))
+            }
+        };
+
+        let mut credentials = Credentials::new(access_key_id, access_key_secret);
+        if let Some(token) = values
+            .remove("ststoken")
+            .or_else(|| values.remove("securitytoken"))
+            .filter(|token| !token.is_empty())
+        {
+            credentials = credentials.with_security_token(token);
+        }
+        Ok(credentials)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct TemporaryCredentials {
+    access_key_id: String,
+    access_key_secret: String,
+    security_token: String,
+    expiration: String,
+}
+
+impl TryFrom<TemporaryCredentials> for Credentials {
+    type Error = Error;
+
+    fn try_from(value: TemporaryCredentials) -> Result<Self, Error> {
+        let expiration = DateTime::parse_from_rfc3339(&value.expiration).map_err(|e| {
+            Error::Credentials(CredentialsError::FetchError {
+                msg: format!("invalid expiration {}: {}", value.expiration, e),
+            })
+        })?;
+        Ok(
+            Credentials::new(value.access_key_id, value.access_key_secret)
+                .with_security_token(value.security_token)
+                .with_expiration(expiration.with_timezone(&Utc)),
+        )
+    }
+}
+
+/// Temporary credentials of the RAM role attached to an ECS instance, fetched
+/// from the instance metadata service.
+#[derive(Clone, Debug)]
+pub struct EcsRamRoleProvider {
+    endpoint: String,
+    role_name: Option<String>,
+    http: HttpClients,
+}
+
+impl EcsRamRoleProvider {
+    /// `role_name` is discovered from the metadata service when not given.
+    pub fn new<S>(role_name: Option<S>) -> Self
+    where
+        S: Into<String>,
+    {
+        EcsRamRoleProvider {
+            endpoint: ECS_METADATA_ENDPOINT.to_string(),
+            role_name: role_name.map(Into::into),
+            http: HttpClients::new(ECS_CONNECT_TIMEOUT, ECS_TIMEOUT),
+        }
+    }
+
+    /// Uses the `ALIBABA_CLOUD_ECS_METADATA` role name if it is set.
+    pub fn from_env() -> Self {
+        Self::new(env::var("ALIBABA_CLOUD_ECS_METADATA").ok())
+    }
+
+    /// Override the metadata endpoint, e.g. to point at a local stand-in.
+    pub fn with_endpoint<S>(mut self, endpoint: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Timeouts of the requests to the metadata service, 1 second to connect
+    /// and 5 seconds in total by default.
+    pub fn with_timeouts(mut self, connect_timeout: StdDuration, timeout: StdDuration) -> Self {
+        self.http = HttpClients::new(connect_timeout, timeout);
+        self
+    }
+}
+
+impl EcsRamRoleProvider {
+    fn role_url(&self, role_name: &str) -> String {
+        format!("{}/{}", self.endpoint.trim_end_matches('/'), role_name)
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for EcsRamRoleProvider {
+    fn credentials(&self) -> Result<Credentials, Error> {
+        let provider = self.clone();
+        let body = fetch(move || {
+            let client = provider.http.blocking();
+            let role_name = match &provider.role_name {
+                Some(role_name) => role_name.clone(),
+                None => first_line(
+                    client
+                        .get(provider.role_url(""))
+                        .send()?
+                        .error_for_status()?
+                        .text()?,
+                ),
+            };
+            Ok(client
+                .get(provider.role_url(&role_name))
+                .send()?
+                .error_for_status()?
+                .text()?)
+        })?;
+        parse_response::<TemporaryCredentials>("ecs metadata", &body)?.try_into()
+    }
+
+    async fn credentials_async(&self) -> Result<Credentials, Error> {
+        let client = &self.http.client;
+        let role_name = match &self.role_name {
+            Some(role_name) => role_name.clone(),
+            None => first_line(
+                client
+                    .get(self.role_url(""))
+                    .send()
+                    .await?
+                    .error_for_status()?
+                    .text()
+                    .await?,
+            ),
+        };
+        let body = client
+            .get(self.role_url(&role_name))
+            .send()
+            .await?
+            .error_for_status()?
+            .text()
+            .await?;
+        parse_response::<TemporaryCredentials>("ecs metadata", &body)?.try_into()
+    }
+}
+
+fn first_line(body: String) -> String {
+    body.lines().next().unwrap_or_default().trim().to_string()
+}
+
+/// Exchanges an OIDC token file for temporary credentials with STS
+/// `AssumeRoleWithOIDC`, as used by RRSA on ACK.
+#[derive(Clone, Debug)]
+pub struct OidcRoleProvider {
+    role_arn: String,
+    oidc_provider_arn: String,
+    oidc_token_file: PathBuf,
+    role_session_name: String,
+    duration_seconds: u64,
+    sts_endpoint: String,
+    http: HttpClients,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct AssumeRoleResponse {
+    credentials: Option<TemporaryCredentials>,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl OidcRoleProvider {
+    pub fn new<S, P>(role_arn: S, oidc_provider_arn: S, oidc_token_file: P) -> Self
+    where
+        S: Into<String>,
+        P: Into<PathBuf>,
+    {
+        OidcRoleProvider {
+            role_arn: role_arn.into(),
+            oidc_provider_arn: oidc_provider_arn.into(),
+            oidc_token_file: oidc_token_file.into(),
+            role_session_name: format!("oss-rust-sdk-{}", Utc::now().timestamp()),
+            duration_seconds: 3600,
+            sts_endpoint: STS_ENDPOINT.to_string(),
+            http: HttpClients::new(STS_CONNECT_TIMEOUT, STS_TIMEOUT),
+        }
+    }
+
+    /// Reads `ALIBABA_CLOUD_ROLE_ARN`, `ALIBABA_CLOUD_OIDC_PROVIDER_ARN`,
+    /// `ALIBABA_CLOUD_OIDC_TOKEN_FILE` and the optional `ALIBABA_CLOUD_ROLE_SESSION_NAME`.
+    pub fn from_env() -> Result<Self, Error> {
+        let var = |name: &str| {
+            env::var(name).map_err(|_| {
+                Error::Credentials(CredentialsError::Missing {
+                    msg: format!("{} is not set in the environment", name),
+                })
+            })
+        };
+        let mut provider = Self::new(
+            var("ALIBABA_CLOUD_ROLE_ARN")?,
+            var("ALIBABA_CLOUD_OIDC_PROVIDER_ARN")?,
+            var("ALIBABA_CLOUD_OIDC_TOKEN_FILE")?,
+        );
+        if let Ok(role_session_name) = env::var("ALIBABA_CLOUD_ROLE_SESSION_NAME") {
+            provider.role_session_name = role_session_name;
+        }
+        Ok(provider)
+    }
+
+    pub fn with_role_session_name<S>(mut self, role_session_name: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.role_session_name = role_session_name.into();
+        self
+    }
+
+    pub fn with_duration_seconds(mut self, duration_seconds: u64) -> Self {
+        self.duration_seconds = duration_seconds;
+        self
+    }
+
+    /// Override the STS endpoint, e.g. a VPC endpoint or a local stand-in.
+    pub fn with_sts_endpoint<S>(mut self, sts_endpoint: S) -> Self
+    where
+        S: Into<String>,
+    {
+        self.sts_endpoint = sts_endpoint.into();
+        self
+    }
+
+    /// Timeouts of the requests to STS, 5 seconds to connect and 10 seconds
+    /// in total by default.
+    pub fn with_timeouts(mut self, connect_timeout: StdDuration, timeout: StdDuration) -> Self {
+        self.http = HttpClients::new(connect_timeout, timeout);
+        self
+    }
+}
+
+impl OidcRoleProvider {
+    fn form(&self) -> Result<Vec<(&'static str, String)>, Error> {
+        let oidc_token = fs::read_to_string(&self.oidc_token_file)?;
+        Ok(vec![
+            ("Action", "AssumeRoleWithOIDC".to_string()),
+            ("Format", "JSON".to_string()),
+            ("Version", "2015-04-01".to_string()),
+            (
+                "Timestamp",
+                Utc::now().format("%Y-%m-%dT%H:%M:%SZ").to_string(),
+            ),
+            ("RoleArn", self.role_arn.clone()),
+            ("OIDCProviderArn", self.oidc_provider_arn.clone()),
+            ("OIDCToken", oidc_token.trim().to_string()),
+            ("RoleSessionName", self.role_session_name.clone()),
+            ("DurationSeconds", self.duration_seconds.to_string()),
+        ])
+    }
+}
+
+#[async_trait]
+impl CredentialsProvider for OidcRoleProvider {
+    fn credentials(&self) -> Result<Credentials, Error> {
+        let form = self.form()?;
+        let http = self.http.clone();
+        let sts_endpoint = self.sts_endpoint.clone();
+        let body = fetch(move || {
+            Ok(http
+                .blocking()
+                .post(sts_endpoint)
+                .form(&form)
+                .send()?
+                .text()?)
+        })?;
+        assume_role_credentials(&body)
+    }
+
+    async fn credentials_async(&self) -> Result<Credentials, Error> {
+        let body = self
+            .http
+            .client
+            .post(&self.sts_endpoint)
+            .form(&self.form()?)
+            .send()
+            .await?
+            .text()
+            .await?;
+        assume_role_credentials(&body)
+    }
+}
+
+fn assume_role_credentials(body: &str) -> Result<Credentials, Error> {
+    let resp = parse_response::<AssumeRoleResponse>("sts", body)?;
+    match resp.credentials {
+        Some(temporary) => temporary.try_into(),
+        None => Err(Error::Credentials(CredentialsError::FetchError {
+            msg: format!(
+                "assume role with oidc failed, code: {}, message: {}",
+                resp.code.unwrap_or_default(),
+                resp.message.unwrap_or_default()
+            ),
+        })),
+    }
+}
+
+/// Credentials returned by a user supplied function.
+pub struct CallbackProvider<F> {
+    callback: F,
+}
+
+impl<F> CallbackProvider<F>
+where
+    F: Fn() -> Result<Credentials, Error> + Send + Sync,
+{
+    pub fn new(callback: F) -> Self {
+        CallbackProvider { callback }
+    }
+}
+
+impl<F> fmt::Debug for CallbackProvider<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CallbackProvider").finish()
+    }
+}
+
+impl<F> CredentialsProvider for CallbackProvider<F>
+where
+    F: Fn() -> Result<Credentials, Error> + Send + Sync,
+{
+    fn credentials(&self) -> Result<Credentials, Error> {
+        (self.callback)()
+    }
+}
+
+/// Caches the credentials of another provider until they are about to expire.
+///
+/// Once the cached credentials are within `refresh_ahead` of their expiration
+/// they are refreshed on a background thread while callers keep using the
+/// still valid ones; callers only wait when nothing valid is cached, async
+/// callers by awaiting the `credentials_async` of the inner provider.
+#[derive(Clone, Debug)]
+pub struct CachedProvider<P> {
+    inner: Arc<CachedInner<P>>,
+}
+
+#[derive(Debug)]
+struct CachedInner<P> {
+    provider: P,
+    refresh_ahead: Duration,
+    cached: RwLock<Option<Credentials>>,
+    refreshing: AtomicBool,
+    refresh_lock: Mutex<()>,
+    // the lock of the blocking callers can not be held across an await
+    async_refresh_lock: futures_util::lock::Mutex<()>,
+}
+
+impl<P> CachedProvider<P>
+where
+    P: CredentialsProvider + 'static,
+{
+    /// Refresh 5 minutes ahead of the expiration.
+    pub fn new(provider: P) -> Self {
+        Self::with_refresh_ahead(provider, Duration::minutes(5))
+    }
+
+    pub fn with_refresh_ahead(provider: P, refresh_ahead: Duration) -> Self {
+        CachedProvider {
+            inner: Arc::new(CachedInner {
+                provider,
+                refresh_ahead,
+                cached: RwLock::new(None),
+                refreshing: AtomicBool::new(false),
+                refresh_lock: Mutex::new(()),
+                async_refresh_lock: futures_util::lock::Mutex::new(()),
+            }),
+        }
+    }
+
+    fn refresh_in_background(&self) {
+        if self
+            .inner
+            .refreshing
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return;
+        }
+        let inner = self.inner.clone();
+        thread::spawn(move || {
+            if let Err(e) = inner.refresh(inner.refresh_ahead) {
+                warn!("refresh credentials failed: {}", e);
+            }
+            inner.refreshing.store(false, Ordering::Release);
+        });
+    }
+
+    /// The cached credentials unless they are expired, starting a background
+    /// refresh when they are about to.
+    fn valid_cached(&self) -> Option<Credentials> {
+        let credentials = self.inner.cached()?;
+        if credentials.expires_within(Duration::zero()) {
+            return None;
+        }
+        if credentials.expires_within(self.inner.refresh_ahead) {
+            self.refresh_in_background();
+        }
+        Some(credentials)
+    }
+}
+
+impl<P> CachedInner<P>
+where
+    P: CredentialsProvider,
+{
+    fn cached(&self) -> Option<Credentials> {
+        self.cached.read().ok().and_then(|cached| cached.clone())
+    }
+
+    /// Fetch new credentials unless the cached ones are valid for longer than
+    /// `threshold`.
+    fn refresh(&self, threshold: Duration) -> Result<Credentials, Error> {
+        let _guard = self.refresh_lock.lock().unwrap_or_else(|e| e.into_inner());
+        // Another caller may have refreshed while we were waiting for the lock
+        if let Some(credentials) = self.cached() {
+            if !credentials.expires_within(threshold) {
+                return Ok(credentials);
+            }
+        }
+
+        let credentials = self.provider.credentials()?;
+        self.store(&credentials);
+        Ok(credentials)
+    }
+
+    async fn refresh_async(&self) -> Result<Credentials, Error> {
+        let _guard = self.async_refresh_lock.lock().await;
+        if let Some(credentials) = self.cached() {
+            if !credentials.expires_within(Duration::zero()) {
+                return Ok(credentials);
+            }
+        }
+
+        let credentials = self.provider.credentials_async().await?;
+        self.store(&credentials);
+        Ok(credentials)
+    }
+
+    fn store(&self, credentials: &Credentials) {
+        if let Ok(mut cached) = self.cached.write() {
+            *cached = Some(credentials.clone());
+        }
+    }
+}
+
+#[async_trait]
+impl<P> CredentialsProvider for CachedProvider<P>
+where
+    P: CredentialsProvider + 'static,
+{
+    fn credentials(&self) -> Result<Credentials, Error> {
+        match self.valid_cached() {
+            Some(credentials) => Ok(credentials),
+            None => self.inner.refresh(Duration::zero()),
+        }
+    }
+
+    async fn credentials_async(&self) -> Result<Credentials, Error> {
+        match self.valid_cached() {
+            Some(credentials) => Ok(credentials),
+            None => self.inner.refresh_async().await,
+        }
+    }
+}
+
+/// Http clients of a provider, shared by its clones. The blocking one is
+/// built on the first blocking fetch, in the thread of `fetch`.
+#[derive(Clone, Debug)]
+struct HttpClients {
+    connect_timeout: StdDuration,
+    timeout: StdDuration,
+    client: reqwest::Client,
+    blocking: Arc<OnceLock<reqwest::blocking::Client>>,
+}
+
+impl HttpClients {
+    fn new(connect_timeout: StdDuration, timeout: StdDuration) -> Self {
+        let client = reqwest::Client::builder()
+            .connect_timeout(connect_timeout)
+            .timeout(timeout)
+            .build()
+            .expect("Build http client failed");
+        HttpClients {
+            connect_timeout,
+            timeout,
+            client,
+            blocking: Arc::new(OnceLock::new()),
+        }
+    }
+
+    fn blocking(&self) -> &reqwest::blocking::Client {
+        self.blocking.get_or_init(|| {
+            reqwest::blocking::Client::builder()
+                .connect_timeout(self.connect_timeout)
+                .timeout(self.timeout)
+                .build()
+                .expect("Build blocking http client failed")
+        })
+    }
+}
+
+/// Parse the JSON response of a credentials endpoint. The body may hold
+/// secrets, so the error only tells where parsing failed.
+fn parse_response<T>(source: &str, body: &str) -> Result<T, Error>
+where
+    T: serde::de::DeserializeOwned,
+{
+    serde_json::from_str(body).map_err(|e| {
+        Error::Credentials(CredentialsError::FetchError {
+            msg: format!(
+                "invalid {} response of {} bytes, {:?} error at line {} column {}",
+                source,
+                body.len(),
+                e.classify(),
+                e.line(),
+                e.column()
+            ),
+        })
+    })
+}
+
+/// Run a blocking http fetch on its own thread, so the providers also work
+/// when called from inside an async runtime.
+fn fetch<F>(f: F) -> Result<String, Error>
+where
+    F: FnOnce() -> Result<String, Error> + Send + 'static,
+{
+    thread::spawn(f).join().unwrap_or_else(|_| {
+        Err(Error::Credentials(CredentialsError::FetchError {
+            msg: "credentials fetching thread panicked".to_string(),
+        }))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{self, Response};
+    use std::sync::atomic::AtomicUsize;
+
+    const ECS_CREDENTIALS: &str = r#"{
+        "AccessKeyId": "STS.ecs",
+        "AccessKeySecret": "ecs-secret",
+        "Expiration": "2030-11-01T05:20:01Z",
+        "SecurityToken": "ecs-token",
+        "LastUpdated": "2030-10-31T23:20:01Z",
+        "Code": "Success"
+    }"#;
+
+    const STS_CREDENTIALS: &str = r#"{
+        "RequestId": "3D57EAD2-8723-1F26-B69C-F8707D8B565D",
+        "AssumedRoleUser": {
+            "AssumedRoleId": "33157794895460****",
+            "Arn": "acs:ram::113511544585****:role/testrole/TestOIDCAssumedRoleSession"
+        },
+        "Credentials": {
+            "SecurityToken": "sts-token",
+            "Expiration": "2030-10-20T04:27:09Z",
+            "AccessKeySecret": "sts-secret",
+            "AccessKeyId": "STS.oidc"
+        }
+    }"#;
+
+    /// Local stand-in of a metadata or STS server, answering `routes` by
+    /// path. Returns the base url and the requests received, each as the
+    /// request line followed by the body.
+    fn serve(routes: Vec<(&'static str, &'static str)>) -> (String, Arc<Mutex<Vec<String>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        let url = test_server::serve(move |request| {
+            received.lock().unwrap().push(format!(
+                "{} {} HTTP/1.1\r\n{}",
+                request.method,
+                request.target,
+                request.body_str()
+            ));
+            match routes.iter().find(|(p, _)| *p == request.target) {
+                Some((_, body)) => Response::ok(*body),
+                None => Response::status(404),
+            }
+        });
+        (url, requests)
+    }
+
+    fn assert_ecs_credentials(credentials: &Credentials) {
+        assert_eq!(credentials.access_key_id(), "STS.ecs");
+        assert_eq!(credentials.access_key_secret(), "ecs-secret");
+        assert_eq!(credentials.security_token(), Some("ecs-token"));
+        assert_eq!(
+            credentials.expiration().unwrap().to_rfc3339(),
+            "2030-11-01T05:20:01+00:00"
+        );
+    }
+
+    fn oidc_provider(sts_endpoint: &str, name: &str) -> OidcRoleProvider {
+        let token_file = env::temp_dir().join(format!(
+            "oss-rust-sdk-{}-{}.token",
+            name,
+            std::process::id()
+        ));
+        fs::write(&token_file, "oidc-token\n").unwrap();
+        OidcRoleProvider::new(
+            "acs:ram::113511544585:role/testrole",
+            "acs:ram::113511544585:oidc-provider/TestOidcProvider",
+            token_file,
+        )
+        .with_role_session_name("test-session")
+        .with_sts_endpoint(sts_endpoint)
+    }
+
+    #[test]
+    fn ecs_ram_role_discovers_the_role() {
+        let (url, requests) = serve(vec![
+            ("/ram/security-credentials/", "my-role\n"),
+            ("/ram/security-credentials/my-role", ECS_CREDENTIALS),
+        ]);
+        let provider = EcsRamRoleProvider::new(None::<String>)
+            .with_endpoint(format!("{}/ram/security-credentials", url));
+
+        assert_ecs_credentials(&provider.credentials().unwrap());
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("GET /ram/security-credentials/ "));
+        assert!(requests[1].starts_with("GET /ram/security-credentials/my-role "));
+    }
+
+    #[tokio::test]
+    async fn ecs_ram_role_async() {
+        let (url, requests) = serve(vec![("/my-role", ECS_CREDENTIALS)]);
+        let provider = EcsRamRoleProvider::new(Some("my-role")).with_endpoint(url);
+
+        assert_ecs_credentials(&provider.credentials_async().await.unwrap());
+        // the blocking path also works inside a runtime
+        assert_ecs_credentials(&provider.credentials().unwrap());
+        assert_eq!(requests.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn invalid_response_does_not_leak_the_body() {
+        let (url, _) = serve(vec![(
+            "/my-role",
+            r#"{"AccessKeyId": "STS.ecs", "AccessKeySecret": "ecs-secret"}"#,
+        )]);
+        let provider = EcsRamRoleProvider::new(Some("my-role")).with_endpoint(url);
+
+        let msg = provider.credentials().unwrap_err().to_string();
+        assert!(msg.contains("invalid ecs metadata response"), "{}", msg);
+        assert!(!msg.contains("ecs-secret"), "{}", msg);
+    }
+
+    #[test]
+    fn oidc_role_assumes_the_role() {
+        let (url, requests) = serve(vec![("/", STS_CREDENTIALS)]);
+        let provider = oidc_provider(&url, "assume");
+
+        let credentials = provider.credentials().unwrap();
+        assert_eq!(credentials.access_key_id(), "STS.oidc");
+        assert_eq!(credentials.access_key_secret(), "sts-secret");
+        assert_eq!(credentials.security_token(), Some("sts-token"));
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].starts_with("POST / "));
+        for param in [
+            "Action=AssumeRoleWithOIDC",
+            "OIDCToken=oidc-token&",
+            "RoleSessionName=test-session",
+            "DurationSeconds=3600",
+        ] {
+            assert!(requests[0].contains(param), "{}", requests[0]);
+        }
+    }
+
+    #[tokio::test]
+    async fn oidc_role_async_reports_sts_errors() {
+        let (url, _) = serve(vec![(
+            "/",
+            r#"{"RequestId": "5A3F6C1C", "Code": "AuthenticationFail.OIDCToken.Expired",
+                "Message": "The OIDC token has expired."}"#,
+        )]);
+        let provider = oidc_provider(&url, "expired");
+
+        let msg = provider.credentials_async().await.unwrap_err().to_string();
+        assert!(
+            msg.contains("code: AuthenticationFail.OIDCToken.Expired"),
+            "{}",
+            msg
+        );
+    }
+
+    #[tokio::test]
+    async fn cached_provider_fetches_once() {
+        let fetched = Arc::new(AtomicUsize::new(0));
+        let counter = fetched.clone();
+        let provider = CachedProvider::new(CallbackProvider::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(Credentials::new("ak", "sk").with_expiration(Utc::now() + Duration::hours(1)))
+        }));
+
+        for _ in 0..3 {
+            assert_eq!(
+                provider.credentials_async().await.unwrap().access_key_id(),
+                "ak"
+            );
+            assert_eq!(provider.credentials().unwrap().access_key_id(), "ak");
+        }
+        assert_eq!(fetched.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn cached_provider_fetches_expired_credentials_again() {
+        let fetched = Arc::new(AtomicUsize::new(0));
+        let counter = fetched.clone();
+        let provider = CachedProvider::new(CallbackProvider::new(move || {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Ok(Credentials::new("ak", "sk").with_expiration(Utc::now() - Duration::seconds(1)))
+        }));
+
+        provider.credentials().unwrap();
+        provider.credentials().unwrap();
+        assert_eq!(fetched.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn unresponsive_endpoint_times_out() {
+        // connections are accepted by the kernel, but never answered
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let timeout = StdDuration::from_millis(200);
+        let provider = EcsRamRoleProvider::new(Some("my-role"))
+            .with_endpoint(&url)
+            .with_timeouts(timeout, timeout);
+
+        let started = std::time::Instant::now();
+        assert!(provider.credentials_async().await.is_err());
+        assert!(provider.credentials().is_err());
+        assert!(started.elapsed() < StdDuration::from_secs(5));
+
+        let provider = oidc_provider(&url, "timeout").with_timeouts(timeout, timeout);
+        assert!(provider.credentials_async().await.is_err());
+        assert!(started.elapsed() < StdDuration::from_secs(5));
+        drop(listener);
+    }
+}
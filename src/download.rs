@@ -0,0 +1,529 @@
+use std::collections::HashMap;
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use futures_util::{stream, StreamExt, TryStreamExt};
+use reqwest::header::{HeaderMap, CONTENT_LENGTH, ETAG, IF_MATCH, RANGE};
+use tokio::fs::OpenOptions;
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+
+use crate::checkpoint::{self, DownloadCheckpoint, DownloadedPart};
+use crate::crc64::{self, Crc64};
+use crate::errors::{Error, ObjectError, ServiceErrorCode};
+use crate::multi_part::DEFAULT_PART_SIZE;
+use crate::oss::{RequestType, OSS};
+use crate::request::Request;
+use crate::retry::RetryPolicy;
+
+/// Downloads an object into a file, several byte ranges at a time.
+///
+/// The ranges are written into a temporary file next to the destination,
+/// which is renamed into place once its CRC64 matches the one of the object.
+/// Every range is requested with `If-Match`, so the download fails when the
+/// object changes in the meantime.
+///
+/// With a [checkpoint](Downloader::checkpoint) file, an interrupted download
+/// keeps the temporary file and resumes from the missing ranges.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn demo(oss: oss_rust_sdk::oss::OSS<'_>) -> Result<(), oss_rust_sdk::errors::Error> {
+/// use oss_rust_sdk::download::Downloader;
+///
+/// Downloader::new(&oss)
+///     .concurrency(8)
+///     .checkpoint("/tmp/big_file.checkpoint")
+///     .download_to_file("object", "/tmp/big_file")
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Downloader {
+    oss: OSS<'static>,
+    part_size: u64,
+    concurrency: usize,
+    checkpoint: Option<PathBuf>,
+}
+
+impl Downloader {
+    pub fn new(oss: &OSS) -> Self {
+        let mut oss = oss.to_owned_client();
+        if oss.retry_policy().is_none() {
+            oss.set_retry_policy(Some(RetryPolicy::default()));
+        }
+        Downloader {
+            oss,
+            part_size: DEFAULT_PART_SIZE,
+            concurrency: 4,
+            checkpoint: None,
+        }
+    }
+
+    /// Size of the ranges, 8 MiB by default.
+    pub fn part_size(mut self, part_size: u64) -> Self {
+        self.part_size = part_size.max(1);
+        self
+    }
+
+    /// Number of ranges downloaded at the same time, 4 by default.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Retry policy of every range, instead of the one of the client.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.oss.set_retry_policy(Some(retry_policy));
+        self
+    }
+
+    /// Save the progress in `path`, to resume the download later.
+    ///
+    /// The checkpoint is reused when it was saved for the same object with the
+    /// same ETag and size, and is removed once the download completes.
+    pub fn checkpoint<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.checkpoint = Some(path.into());
+        self
+    }
+
+    pub async fn download_to_file<S, P>(&self, object_name: S, file: P) -> Result<(), Error>
+    where
+        S: AsRef<str>,
+        P: AsRef<Path>,
+    {
+        let file = file.as_ref();
+        let mut temp = PathBuf::from(file);
+        temp.as_mut_os_string().push(".download");
+
+        let changed = AtomicBool::new(false);
+        let result = self
+            .download(object_name.as_ref(), file, &temp, &changed)
+            .await;
+        // progress is only worth keeping for a later resume of the same object
+        if result.is_err() && (self.checkpoint.is_none() || changed.load(Ordering::SeqCst)) {
+            self.discard(&temp).await;
+        }
+        result
+    }
+
+    async fn download(
+        &self,
+        object_name: &str,
+        file: &Path,
+        temp: &Path,
+        changed: &AtomicBool,
+    ) -> Result<(), Error> {
+        let resp = self
+            .oss
+            .send(Request::new(
+                RequestType::Head,
+                object_name,
+                None::<HashMap<String, String>>,
+                None,
+            ))
+            .await?;
+        let headers = resp.headers();
+        let size: u64 = header_str(headers, CONTENT_LENGTH.as_str())?
+            .parse()
+            .map_err(|e| get_error(format!("cannot parse to number: {}", e)))?;
+        let e_tag = header_str(headers, ETAG.as_str())?.to_string();
+        let crc64 = headers
+            .get("x-oss-hash-crc64ecma")
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<u64>().ok());
+
+        let mut checkpoint = match self
+            .load_checkpoint(object_name, &e_tag, size, temp)
+            .await?
+        {
+            Some(checkpoint) => checkpoint,
+            None => {
+                let f = OpenOptions::new()
+                    .write(true)
+                    .create(true)
+                    .truncate(true)
+                    .open(temp)
+                    .await?;
+                f.set_len(size).await?;
+                let checkpoint = DownloadCheckpoint {
+                    bucket: self.oss.bucket().to_string(),
+                    object_name: object_name.to_string(),
+                    e_tag: e_tag.clone(),
+                    size,
+                    part_size: self.part_size,
+                    parts: Vec::new(),
+                };
+                self.save_checkpoint(&checkpoint).await?;
+                checkpoint
+            }
+        };
+
+        let part_size = checkpoint.part_size;
+        let missing: Vec<usize> = (1..=size.div_ceil(part_size) as usize)
+            .filter(|n| !checkpoint.parts.iter().any(|p| p.part_number == *n))
+            .collect();
+        let mut downloads = std::pin::pin!(stream::iter(missing)
+            .map(|part_number| {
+                let range = part_range(part_number, part_size, size);
+                self.download_part(object_name, &e_tag, temp, part_number, range, changed)
+            })
+            .buffer_unordered(self.concurrency));
+        while let Some(part) = downloads.try_next().await? {
+            checkpoint.parts.push(part);
+            self.save_checkpoint(&checkpoint).await?;
+        }
+
+        checkpoint.parts.sort_by_key(|p| p.part_number);
+        let crc = checkpoint.parts.iter().fold(0, |crc, p| {
+            let (start, end) = part_range(p.part_number, part_size, size);
+            crc64::combine(crc, p.crc64, end - start + 1)
+        });
+        if let Some(expected) = crc64 {
+            if crc != expected {
+                // a corrupted range can not be told apart, start over next time
+                changed.store(true, Ordering::SeqCst);
+                return Err(get_error(format!(
+                    "CRC64 of {} is {}, expected {}",
+                    object_name, crc, expected
+                )));
+            }
+        }
+
+        tokio::fs::rename(temp, file).await?;
+        if let Some(path) = &self.checkpoint {
+            checkpoint::remove(path).await?;
+        }
+        Ok(())
+    }
+
+    /// Download the inclusive byte `range` into the temporary file.
+    async fn download_part(
+        &self,
+        object_name: &str,
+        e_tag: &str,
+        temp: &Path,
+        part_number: usize,
+        (start, end): (u64, u64),
+        changed: &AtomicBool,
+    ) -> Result<DownloadedPart, Error> {
+        let request = Request::new(
+            RequestType::Get,
+            object_name,
+            None::<HashMap<String, String>>,
+            None,
+        )
+        .header(RANGE.as_str(), format!("bytes={}-{}", start, end))
+        .header(IF_MATCH.as_str(), e_tag);
+        let resp = match self.oss.send(request).await {
+            Ok(resp) => resp,
+            Err(e) => {
+                if matches!(
+                    e.service_error_code(),
+                    Some(ServiceErrorCode::PreconditionFailed)
+                ) {
+                    changed.store(true, Ordering::SeqCst);
+                }
+                return Err(e);
+            }
+        };
+        if resp.headers().get(ETAG).map(|v| v.as_bytes()) != Some(e_tag.as_bytes()) {
+            changed.store(true, Ordering::SeqCst);
+            return Err(get_error(format!(
+                "ETag of {} changed during the download",
+                object_name
+            )));
+        }
+
+        let mut file = OpenOptions::new().write(true).open(temp).await?;
+        file.seek(SeekFrom::Start(start)).await?;
+        let mut crc = Crc64::new();
+        let mut written = 0;
+        let mut body = resp.bytes_stream();
+        while let Some(chunk) = body.try_next().await? {
+            crc.update(&chunk);
+            file.write_all(&chunk).await?;
+            written += chunk.len() as u64;
+        }
+        if written != end - start + 1 {
+            return Err(get_error(format!(
+                "range {}-{} of {} returned {} bytes",
+                start, end, object_name, written
+            )));
+        }
+        file.sync_data().await?;
+
+        Ok(DownloadedPart {
+            part_number,
+            crc64: crc.value(),
+        })
+    }
+
+    /// The saved checkpoint, when its temporary file is still there.
+    async fn load_checkpoint(
+        &self,
+        object_name: &str,
+        e_tag: &str,
+        size: u64,
+        temp: &Path,
+    ) -> Result<Option<DownloadCheckpoint>, Error> {
+        let path = match &self.checkpoint {
+            Some(path) => path,
+            None => return Ok(None),
+        };
+        let checkpoint: DownloadCheckpoint = match checkpoint::load(path).await? {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(None),
+        };
+        if checkpoint.bucket != self.oss.bucket()
+            || checkpoint.object_name != object_name
+            || checkpoint.e_tag != e_tag
+            || checkpoint.size != size
+            || checkpoint.part_size == 0
+        {
+            warn!(
+                "checkpoint {} is for another object, start over",
+                path.display()
+            );
+            return Ok(None);
+        }
+        match tokio::fs::metadata(temp).await {
+            Ok(metadata) if metadata.len() == size => Ok(Some(checkpoint)),
+            _ => Ok(None),
+        }
+    }
+
+    async fn save_checkpoint(&self, checkpoint: &DownloadCheckpoint) -> Result<(), Error> {
+        match &self.checkpoint {
+            Some(path) => checkpoint::save(path, checkpoint).await,
+            None => Ok(()),
+        }
+    }
+
+    async fn discard(&self, temp: &Path) {
+        if let Err(e) = checkpoint::remove(temp).await {
+            warn!("remove {}: {}", temp.display(), e);
+        }
+        if let Some(path) = &self.checkpoint {
+            if let Err(e) = checkpoint::remove(path).await {
+                warn!("remove {}: {}", path.display(), e);
+            }
+        }
+    }
+}
+
+/// Inclusive byte range of a part, numbered from 1.
+fn part_range(part_number: usize, part_size: u64, size: u64) -> (u64, u64) {
+    let start = (part_number as u64 - 1) * part_size;
+    (start, (start + part_size).min(size) - 1)
+}
+
+fn header_str<'h>(headers: &'h HeaderMap, key: &str) -> Result<&'h str, Error> {
+    headers
+        .get(key)
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| get_error(format!("can not find {} in head response", key)))
+}
+
+fn get_error(msg: String) -> Error {
+    Error::Object(ObjectError::GetError { msg })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{self, Received, Response};
+    use std::env;
+    use std::sync::{Arc, Mutex};
+
+    const E_TAG: &str = "\"5B3C1A2E053D763E1B002CC607C5A0FE\"";
+
+    fn data() -> Vec<u8> {
+        (0..1000u32).map(|i| (i * 7 % 256) as u8).collect()
+    }
+
+    fn crc(buf: &[u8]) -> u64 {
+        let mut crc = Crc64::new();
+        crc.update(buf);
+        crc.value()
+    }
+
+    /// Stand-in serving `data` with the ETag `E_TAG` to HEAD requests, and
+    /// `get_e_tag` to the ranged GETs, which fail with `PreconditionFailed`
+    /// when it is not their `If-Match`. Returns the client and the requests.
+    fn object_server(
+        data: Vec<u8>,
+        crc64: u64,
+        get_e_tag: &'static str,
+    ) -> (OSS<'static>, Arc<Mutex<Vec<Received>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        let url = test_server::serve(move |request| {
+            received.lock().unwrap().push(request.clone());
+            if request.method == "HEAD" {
+                return Response::ok("")
+                    .header("Content-Length", data.len())
+                    .header("ETag", E_TAG)
+                    .header("x-oss-hash-crc64ecma", crc64);
+            }
+            if request.header("If-Match") != Some(get_e_tag) {
+                return Response::error(412, "PreconditionFailed");
+            }
+            let (start, end) = request
+                .header("Range")
+                .and_then(|r| r.strip_prefix("bytes="))
+                .and_then(|r| r.split_once('-'))
+                .unwrap();
+            let (start, end): (usize, usize) = (start.parse().unwrap(), end.parse().unwrap());
+            Response {
+                status: 206,
+                ..Response::ok(&data[start..=end])
+            }
+            .header("ETag", get_e_tag)
+        });
+        (test_server::client(&url), requests)
+    }
+
+    /// Destination and checkpoint paths in a fresh directory.
+    fn paths(name: &str) -> (PathBuf, PathBuf, PathBuf) {
+        let dir = env::temp_dir().join(format!(
+            "oss-rust-sdk-download-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        (
+            dir.join("object"),
+            dir.join("object.download"),
+            dir.join("object.checkpoint"),
+        )
+    }
+
+    fn ranges(requests: &Mutex<Vec<Received>>) -> Vec<String> {
+        let mut ranges: Vec<String> = requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|r| r.method == "GET")
+            .map(|r| r.header("Range").unwrap().to_string())
+            .collect();
+        ranges.sort_by_key(|r| r[6..].split('-').next().unwrap().parse::<u64>().unwrap());
+        ranges
+    }
+
+    #[tokio::test]
+    async fn downloads_every_range() {
+        let (oss, requests) = object_server(data(), crc(&data()), E_TAG);
+        let (file, temp, _) = paths("full");
+
+        Downloader::new(&oss)
+            .part_size(300)
+            .concurrency(3)
+            .download_to_file("object", &file)
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&file).unwrap(), data());
+        assert!(!temp.exists());
+        assert_eq!(
+            ranges(&requests),
+            [
+                "bytes=0-299",
+                "bytes=300-599",
+                "bytes=600-899",
+                "bytes=900-999"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn resumes_the_missing_ranges() {
+        let data = data();
+        let (oss, requests) = object_server(data.clone(), crc(&data), E_TAG);
+        let (file, temp, checkpoint_path) = paths("resume");
+
+        // the first two ranges of 300 bytes were written before a crash
+        let mut partial = data[..600].to_vec();
+        partial.resize(data.len(), 0);
+        std::fs::write(&temp, partial).unwrap();
+        let checkpoint = DownloadCheckpoint {
+            bucket: "bucket".to_string(),
+            object_name: "object".to_string(),
+            e_tag: E_TAG.to_string(),
+            size: data.len() as u64,
+            part_size: 300,
+            parts: vec![
+                DownloadedPart {
+                    part_number: 2,
+                    crc64: crc(&data[300..600]),
+                },
+                DownloadedPart {
+                    part_number: 1,
+                    crc64: crc(&data[..300]),
+                },
+            ],
+        };
+        checkpoint::save(&checkpoint_path, &checkpoint)
+            .await
+            .unwrap();
+
+        // the part size of the checkpoint wins
+        Downloader::new(&oss)
+            .part_size(100)
+            .checkpoint(&checkpoint_path)
+            .download_to_file("object", &file)
+            .await
+            .unwrap();
+
+        assert_eq!(std::fs::read(&file).unwrap(), data);
+        assert_eq!(ranges(&requests), ["bytes=600-899", "bytes=900-999"]);
+        assert!(!temp.exists());
+        assert!(!checkpoint_path.exists());
+    }
+
+    #[tokio::test]
+    async fn changed_object_discards_the_progress() {
+        let (oss, _) = object_server(data(), crc(&data()), "\"changed\"");
+        let (file, temp, checkpoint_path) = paths("changed");
+
+        let err = Downloader::new(&oss)
+            .part_size(300)
+            .checkpoint(&checkpoint_path)
+            .download_to_file("object", &file)
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.service_error_code(),
+            Some(ServiceErrorCode::PreconditionFailed)
+        );
+        assert!(!file.exists());
+        assert!(!temp.exists());
+        assert!(!checkpoint_path.exists());
+    }
+
+    #[tokio::test]
+    async fn crc_mismatch_fails_before_the_rename() {
+        let (oss, _) = object_server(data(), crc(&data()) ^ 1, E_TAG);
+        let (file, temp, checkpoint_path) = paths("crc");
+
+        let msg = Downloader::new(&oss)
+            .part_size(300)
+            .checkpoint(&checkpoint_path)
+            .download_to_file("object", &file)
+            .await
+            .unwrap_err()
+            .to_string();
+
+        assert!(msg.contains("CRC64 of object"), "{}", msg);
+        assert!(!file.exists());
+        assert!(!temp.exists());
+        assert!(!checkpoint_path.exists());
+    }
+}
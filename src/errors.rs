@@ -1,8 +1,12 @@
 use quick_xml::DeError;
 use quick_xml::Error as QxmlError;
+use reqwest::header::HeaderMap;
 use reqwest::header::InvalidHeaderName as HttpInvalidHeaderNameError;
 use reqwest::header::InvalidHeaderValue as HttpInvalidHeaderValueError;
 use reqwest::Error as ReqwestError;
+use reqwest::StatusCode;
+use serde::Deserialize;
+use serde_json::Error as JsonError;
 use std::error::Error as StdError;
 use std::io::Error as IoError;
 use std::string::FromUtf8Error;
@@ -16,6 +20,9 @@ pub enum Error {
     Qxml(QxmlError),
     Http(HttpError),
     DeserializeError(DeError),
+    Json(JsonError),
+    Credentials(CredentialsError),
+    Service(Box<ServiceError>),
 }
 
 #[derive(Debug, Display)]
@@ -66,6 +73,12 @@ impl From<DeError> for Error {
     }
 }
 
+impl From<JsonError> for Error {
+    fn from(e: JsonError) -> Error {
+        Error::Json(e)
+    }
+}
+
 #[derive(Debug, Display)]
 pub enum ObjectError {
     #[display(fmt = "PUT ERROR: {}", msg)]
@@ -82,4 +95,308 @@ pub enum ObjectError {
     PostError { msg: String },
 }
 
+#[derive(Debug, Display)]
+pub enum CredentialsError {
+    #[display(fmt = "MISSING CREDENTIALS: {}", msg)]
+    Missing { msg: String },
+    #[display(fmt = "FETCH CREDENTIALS ERROR: {}", msg)]
+    FetchError { msg: String },
+}
+
+/// An error returned by OSS, parsed from the `<Error>` body of the response.
+#[derive(Debug, Display)]
+#[display(
+    fmt = "OSS SERVICE ERROR: status: {}, code: {}, message: {}, request id: {}, host id: {}, ec: {}",
+    status,
+    code,
+    message,
+    request_id,
+    host_id,
+    ec
+)]
+pub struct ServiceError {
+    pub status: StatusCode,
+    pub code: String,
+    pub message: String,
+    pub request_id: String,
+    pub host_id: String,
+    pub ec: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ErrorBody {
+    #[serde(default)]
+    code: String,
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    request_id: String,
+    #[serde(default)]
+    host_id: String,
+    #[serde(default, rename = "EC")]
+    ec: String,
+}
+
+impl ServiceError {
+    /// Build the error from a failed response. Responses without a body, like
+    /// the ones of HEAD requests, fall back to the `x-oss-err` header and the
+    /// `x-oss-request-id` and `x-oss-ec` headers.
+    pub fn from_response(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Self {
+        let header = |key: &str| {
+            headers
+                .get(key)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default()
+                .to_string()
+        };
+        let body = if body.is_empty() {
+            base64::decode(header("x-oss-err")).unwrap_or_default()
+        } else {
+            body.to_vec()
+        };
+        let parsed = String::from_utf8(body)
+            .ok()
+            .and_then(|body| quick_xml::de::from_str::<ErrorBody>(&body).ok())
+            .unwrap_or_default();
+
+        let or_else = |value: String, fallback: String| {
+            if value.is_empty() {
+                fallback
+            } else {
+                value
+            }
+        };
+        ServiceError {
+            status,
+            code: parsed.code,
+            message: or_else(
+                parsed.message,
+                status.canonical_reason().unwrap_or_default().to_string(),
+            ),
+            request_id: or_else(parsed.request_id, header("x-oss-request-id")),
+            host_id: parsed.host_id,
+            ec: or_else(parsed.ec, header("x-oss-ec")),
+        }
+    }
+
+    pub fn error_code(&self) -> ServiceErrorCode {
+        ServiceErrorCode::from(self.code.as_str())
+    }
+}
+
+/// Well known OSS error codes, anything else is kept in `Other`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ServiceErrorCode {
+    AccessDenied,
+    BucketAlreadyExists,
+    BucketNotEmpty,
+    EntityTooLarge,
+    EntityTooSmall,
+    FileAlreadyExists,
+    InvalidAccessKeyId,
+    InvalidArgument,
+    InvalidBucketName,
+    InvalidDigest,
+    InvalidObjectName,
+    InvalidPart,
+    InvalidPartOrder,
+    InternalError,
+    MalformedXML,
+    NoSuchBucket,
+    NoSuchKey,
+    NoSuchUpload,
+    NotImplemented,
+    PositionNotEqualToLength,
+    PreconditionFailed,
+    RequestTimeTooSkewed,
+    RequestTimeout,
+    RestoreAlreadyInProgress,
+    SecurityTokenExpired,
+    ServiceUnavailable,
+    SignatureDoesNotMatch,
+    TooManyBuckets,
+    Other(String),
+}
+
+impl From<&str> for ServiceErrorCode {
+    fn from(code: &str) -> Self {
+        match code {
+            "AccessDenied" => ServiceErrorCode::AccessDenied,
+            "BucketAlreadyExists" => ServiceErrorCode::BucketAlreadyExists,
+            "BucketNotEmpty" => ServiceErrorCode::BucketNotEmpty,
+            "EntityTooLarge" => ServiceErrorCode::EntityTooLarge,
+            "EntityTooSmall" => ServiceErrorCode::EntityTooSmall,
+            "FileAlreadyExists" => ServiceErrorCode::FileAlreadyExists,
+            "InvalidAccessKeyId" => ServiceErrorCode::InvalidAccessKeyId,
+            "InvalidArgument" => ServiceErrorCode::InvalidArgument,
+            "InvalidBucketName" => ServiceErrorCode::InvalidBucketName,
+            "InvalidDigest" => ServiceErrorCode::InvalidDigest,
+            "InvalidObjectName" => ServiceErrorCode::InvalidObjectName,
+            "InvalidPart" => ServiceErrorCode::InvalidPart,
+            "InvalidPartOrder" => ServiceErrorCode::InvalidPartOrder,
+            "InternalError" => ServiceErrorCode::InternalError,
+            "MalformedXML" => ServiceErrorCode::MalformedXML,
+            "NoSuchBucket" => ServiceErrorCode::NoSuchBucket,
+            "NoSuchKey" => ServiceErrorCode::NoSuchKey,
+            "NoSuchUpload" => ServiceErrorCode::NoSuchUpload,
+            "NotImplemented" => ServiceErrorCode::NotImplemented,
+            "PositionNotEqualToLength" => ServiceErrorCode::PositionNotEqualToLength,
+            "PreconditionFailed" => ServiceErrorCode::PreconditionFailed,
+            "RequestTimeTooSkewed" => ServiceErrorCode::RequestTimeTooSkewed,
+            "RequestTimeout" => ServiceErrorCode::RequestTimeout,
+            "RestoreAlreadyInProgress" => ServiceErrorCode::RestoreAlreadyInProgress,
+            "SecurityTokenExpired" => ServiceErrorCode::SecurityTokenExpired,
+            "ServiceUnavailable" => ServiceErrorCode::ServiceUnavailable,
+            "SignatureDoesNotMatch" => ServiceErrorCode::SignatureDoesNotMatch,
+            "TooManyBuckets" => ServiceErrorCode::TooManyBuckets,
+            other => ServiceErrorCode::Other(other.to_string()),
+        }
+    }
+}
+
+impl Error {
+    /// The OSS error code, if this error was returned by the service.
+    pub fn service_error_code(&self) -> Option<ServiceErrorCode> {
+        match self {
+            Error::Service(e) => Some(e.error_code()),
+            _ => None,
+        }
+    }
+
+    pub(crate) async fn from_response(resp: reqwest::Response) -> Error {
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().await.unwrap_or_default();
+        Error::Service(Box::new(ServiceError::from_response(
+            status, &headers, &body,
+        )))
+    }
+
+    pub(crate) fn from_blocking_response(resp: reqwest::blocking::Response) -> Error {
+        let status = resp.status();
+        let headers = resp.headers().clone();
+        let body = resp.bytes().unwrap_or_default();
+        Error::Service(Box::new(ServiceError::from_response(
+            status, &headers, &body,
+        )))
+    }
+}
+
 impl StdError for Error {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BODY: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+        <Error>
+          <Code>NoSuchKey</Code>
+          <Message>The specified key does not exist.</Message>
+          <RequestId>5C3D9175B6FC201293AD****</RequestId>
+          <HostId>bucket.oss-cn-hangzhou.aliyuncs.com</HostId>
+          <Key>a.txt</Key>
+          <EC>0026-00000001</EC>
+        </Error>"#;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (k, v) in pairs {
+            headers.insert(*k, v.parse().unwrap());
+        }
+        headers
+    }
+
+    #[test]
+    fn service_error_from_body() {
+        // the body wins over the headers
+        let e = ServiceError::from_response(
+            StatusCode::NOT_FOUND,
+            &headers(&[("x-oss-request-id", "other"), ("x-oss-ec", "other")]),
+            BODY.as_bytes(),
+        );
+        assert_eq!(e.status, StatusCode::NOT_FOUND);
+        assert_eq!(e.code, "NoSuchKey");
+        assert_eq!(e.message, "The specified key does not exist.");
+        assert_eq!(e.request_id, "5C3D9175B6FC201293AD****");
+        assert_eq!(e.host_id, "bucket.oss-cn-hangzhou.aliyuncs.com");
+        assert_eq!(e.ec, "0026-00000001");
+        assert_eq!(e.error_code(), ServiceErrorCode::NoSuchKey);
+    }
+
+    #[test]
+    fn service_error_of_a_head_request() {
+        let e = ServiceError::from_response(
+            StatusCode::NOT_FOUND,
+            &headers(&[("x-oss-err", &base64::encode(BODY))]),
+            b"",
+        );
+        assert_eq!(e.code, "NoSuchKey");
+        assert_eq!(e.request_id, "5C3D9175B6FC201293AD****");
+        assert_eq!(e.ec, "0026-00000001");
+
+        // without x-oss-err, only the headers are left
+        let e = ServiceError::from_response(
+            StatusCode::FORBIDDEN,
+            &headers(&[
+                ("x-oss-request-id", "5C3D9175B6FC201293AD****"),
+                ("x-oss-ec", "0003-00000001"),
+            ]),
+            b"",
+        );
+        assert_eq!(e.code, "");
+        assert_eq!(e.message, "Forbidden");
+        assert_eq!(e.request_id, "5C3D9175B6FC201293AD****");
+        assert_eq!(e.ec, "0003-00000001");
+        assert_eq!(e.error_code(), ServiceErrorCode::Other(String::new()));
+    }
+
+    #[test]
+    fn service_error_from_an_unparseable_body() {
+        for body in [&b"<html>Bad Gateway</html>"[..], b"\xff\xfe"] {
+            let e = ServiceError::from_response(
+                StatusCode::BAD_GATEWAY,
+                &headers(&[("x-oss-request-id", "5C3D9175B6FC201293AD****")]),
+                body,
+            );
+            assert_eq!(e.status, StatusCode::BAD_GATEWAY);
+            assert_eq!(e.code, "");
+            assert_eq!(e.message, "Bad Gateway");
+            assert_eq!(e.request_id, "5C3D9175B6FC201293AD****");
+            assert_eq!(e.host_id, "");
+        }
+    }
+
+    #[test]
+    fn service_error_codes() {
+        for (code, expected) in [
+            ("AccessDenied", ServiceErrorCode::AccessDenied),
+            ("NoSuchUpload", ServiceErrorCode::NoSuchUpload),
+            ("MalformedXML", ServiceErrorCode::MalformedXML),
+            (
+                "RestoreAlreadyInProgress",
+                ServiceErrorCode::RestoreAlreadyInProgress,
+            ),
+            ("TooManyBuckets", ServiceErrorCode::TooManyBuckets),
+            (
+                "NoSuchLifecycle",
+                ServiceErrorCode::Other("NoSuchLifecycle".to_string()),
+            ),
+            // codes are case sensitive
+            (
+                "accessdenied",
+                ServiceErrorCode::Other("accessdenied".to_string()),
+            ),
+        ] {
+            assert_eq!(ServiceErrorCode::from(code), expected);
+        }
+
+        let e = Error::Service(Box::new(ServiceError::from_response(
+            StatusCode::NOT_FOUND,
+            &HeaderMap::new(),
+            BODY.as_bytes(),
+        )));
+        assert_eq!(e.service_error_code(), Some(ServiceErrorCode::NoSuchKey));
+        assert_eq!(Error::from(IoError::other("io")).service_error_code(), None);
+    }
+}
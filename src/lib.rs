@@ -3,14 +3,34 @@ extern crate derive_more;
 #[macro_use]
 extern crate log;
 
+pub mod append;
+pub mod async_bucket;
 pub mod async_object;
 pub mod async_service;
+pub mod bucket;
+#[cfg(feature = "fs")]
+pub mod checkpoint;
+pub mod cors;
+pub mod crc64;
+pub mod credentials;
+#[cfg(feature = "fs")]
+pub mod download;
 pub mod errors;
+pub mod lifecycle;
 pub mod multi_part;
 pub mod object;
+pub mod options;
 pub mod oss;
+pub mod policy;
 pub mod prelude;
+pub mod restore;
+pub mod retry;
 pub mod service;
+pub mod tagging;
+pub mod versioning;
 
 mod auth;
+mod request;
+#[cfg(test)]
+mod test_server;
 mod utils;
@@ -0,0 +1,498 @@
+//! Lifecycle rules of a bucket, expiring objects or moving them to cheaper
+//! storage classes.
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::Error;
+use crate::options::{string_enum, StorageClass};
+use crate::tagging::Tag;
+
+/// Whether a rule is applied.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RuleStatus {
+    Enabled,
+    Disabled,
+}
+
+string_enum! {
+    RuleStatus {
+        Enabled => "Enabled",
+        Disabled => "Disabled",
+    }
+}
+
+/// Body of PutBucketLifecycle and GetBucketLifecycle.
+///
+/// # Examples
+///
+/// ```
+/// use oss_rust_sdk::lifecycle::{LifecycleConfiguration, LifecycleRule};
+/// use oss_rust_sdk::options::StorageClass;
+///
+/// let config = LifecycleConfiguration::new(vec![
+///     LifecycleRule::new("logs", "logs/")
+///         .transition_after_days(30, StorageClass::IA)
+///         .transition_after_days(180, StorageClass::Archive)
+///         .expire_after_days(365)
+///         .abort_multipart_upload_after_days(7),
+///     LifecycleRule::new("tmp", "")
+///         .tag("temporary", "true")
+///         .exclude_prefix("tmp/keep/")
+///         .expire_at("2030-01-01T00:00:00.000Z"),
+///     LifecycleRule::new("hot", "data/")
+///         .transition_after_access_days(30, StorageClass::IA, true)
+///         .noncurrent_expire_after_days(10)
+///         .noncurrent_transition_after_days(3, StorageClass::IA),
+/// ]);
+///
+/// let xml = config.to_xml().unwrap();
+/// assert_eq!(LifecycleConfiguration::from_xml(&xml).unwrap(), config);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LifecycleConfiguration {
+    #[serde(default)]
+    pub rule: Vec<LifecycleRule>,
+}
+
+impl LifecycleConfiguration {
+    pub fn new(rules: Vec<LifecycleRule>) -> Self {
+        LifecycleConfiguration { rule: rules }
+    }
+
+    pub fn rules(&self) -> &[LifecycleRule] {
+        &self.rule
+    }
+
+    pub fn to_xml(&self) -> Result<String, Error> {
+        Ok(quick_xml::se::to_string_with_root(
+            "LifecycleConfiguration",
+            self,
+        )?)
+    }
+
+    pub fn from_xml(body: &str) -> Result<Self, Error> {
+        Ok(quick_xml::de::from_str(body)?)
+    }
+}
+
+/// A rule applied to the objects matching its prefix and tags.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LifecycleRule {
+    #[serde(rename = "ID", default)]
+    pub id: String,
+    /// Empty to match every object.
+    #[serde(default)]
+    pub prefix: String,
+    pub status: RuleStatus,
+    /// Objects must have all of the tags.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tag: Vec<Tag>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filter: Option<LifecycleFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration: Option<Expiration>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub transition: Vec<Transition>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub abort_multipart_upload: Option<AbortMultipartUpload>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub noncurrent_version_expiration: Option<NoncurrentVersionExpiration>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub noncurrent_version_transition: Vec<NoncurrentVersionTransition>,
+    /// Unix time since which access times are tracked, returned by OSS for
+    /// buckets with access tracking.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub atime_base: Option<u64>,
+}
+
+impl LifecycleRule {
+    /// An enabled rule without any action.
+    pub fn new<S1, S2>(id: S1, prefix: S2) -> Self
+    where
+        S1: Into<String>,
+        S2: Into<String>,
+    {
+        LifecycleRule {
+            id: id.into(),
+            prefix: prefix.into(),
+            status: RuleStatus::Enabled,
+            tag: Vec::new(),
+            filter: None,
+            expiration: None,
+            transition: Vec::new(),
+            abort_multipart_upload: None,
+            noncurrent_version_expiration: None,
+            noncurrent_version_transition: Vec::new(),
+            atime_base: None,
+        }
+    }
+
+    pub fn status(mut self, status: RuleStatus) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Only match the objects with this tag.
+    pub fn tag<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.tag.push(Tag {
+            key: key.into(),
+            value: value.into(),
+        });
+        self
+    }
+
+    /// Do not match the objects under `prefix`.
+    pub fn exclude_prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        let filter = self.filter.get_or_insert_with(Default::default);
+        filter.not.push(LifecycleNot {
+            prefix: prefix.into(),
+            tag: None,
+        });
+        self
+    }
+
+    /// Delete the objects `days` after their last modification.
+    pub fn expire_after_days(mut self, days: u32) -> Self {
+        self.expiration = Some(Expiration {
+            days: Some(days),
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Delete the objects last modified before `date`, an ISO 8601 date at
+    /// midnight UTC such as `2030-01-01T00:00:00.000Z`.
+    pub fn expire_at<S: Into<String>>(mut self, date: S) -> Self {
+        self.expiration = Some(Expiration {
+            created_before_date: Some(date.into()),
+            ..Default::default()
+        });
+        self
+    }
+
+    /// Move the objects to `storage_class` `days` after their last modification.
+    pub fn transition_after_days(mut self, days: u32, storage_class: StorageClass) -> Self {
+        self.transition.push(Transition {
+            days: Some(days),
+            ..Transition::new(storage_class)
+        });
+        self
+    }
+
+    /// Move the objects to `storage_class` `days` after they were last read,
+    /// moving them back to `Standard` when read again if `return_to_std_when_visit`.
+    pub fn transition_after_access_days(
+        mut self,
+        days: u32,
+        storage_class: StorageClass,
+        return_to_std_when_visit: bool,
+    ) -> Self {
+        self.transition.push(Transition {
+            days: Some(days),
+            is_access_time: Some(true),
+            return_to_std_when_visit: Some(return_to_std_when_visit),
+            ..Transition::new(storage_class)
+        });
+        self
+    }
+
+    /// Abort the multipart uploads initiated `days` ago.
+    pub fn abort_multipart_upload_after_days(mut self, days: u32) -> Self {
+        self.abort_multipart_upload = Some(AbortMultipartUpload {
+            days: Some(days),
+            created_before_date: None,
+        });
+        self
+    }
+
+    /// Delete the previous versions `days` after they were replaced.
+    pub fn noncurrent_expire_after_days(mut self, days: u32) -> Self {
+        self.noncurrent_version_expiration = Some(NoncurrentVersionExpiration {
+            noncurrent_days: days,
+        });
+        self
+    }
+
+    /// Move the previous versions to `storage_class` `days` after they were replaced.
+    pub fn noncurrent_transition_after_days(
+        mut self,
+        days: u32,
+        storage_class: StorageClass,
+    ) -> Self {
+        self.noncurrent_version_transition
+            .push(NoncurrentVersionTransition {
+                noncurrent_days: days,
+                storage_class,
+                is_access_time: None,
+                return_to_std_when_visit: None,
+            });
+        self
+    }
+}
+
+/// Exclusions of a rule.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LifecycleFilter {
+    #[serde(default)]
+    pub not: Vec<LifecycleNot>,
+}
+
+/// Objects under `prefix`, and with `tag` when set, are not matched.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct LifecycleNot {
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<Tag>,
+}
+
+/// When the matched objects are deleted, by age or date.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Expiration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before_date: Option<String>,
+    /// Remove delete markers without any previous version, on versioned buckets.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expired_object_delete_marker: Option<bool>,
+}
+
+/// When the matched objects are moved to another storage class.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Transition {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before_date: Option<String>,
+    pub storage_class: StorageClass,
+    /// Count `days` from the last access rather than the last modification.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_access_time: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_to_std_when_visit: Option<bool>,
+    /// Also move the objects smaller than 64 KB.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allow_small_file: Option<bool>,
+}
+
+impl Transition {
+    /// A transition to `storage_class`, set `days` or `created_before_date`.
+    pub fn new(storage_class: StorageClass) -> Self {
+        Transition {
+            days: None,
+            created_before_date: None,
+            storage_class,
+            is_access_time: None,
+            return_to_std_when_visit: None,
+            allow_small_file: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct AbortMultipartUpload {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub days: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created_before_date: Option<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct NoncurrentVersionExpiration {
+    pub noncurrent_days: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct NoncurrentVersionTransition {
+    pub noncurrent_days: u32,
+    pub storage_class: StorageClass,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_access_time: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_to_std_when_visit: Option<bool>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // responses of GetBucketLifecycle, as documented by OSS
+    const FILTER_RESPONSE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<LifecycleConfiguration>
+  <Rule>
+    <ID>RuleID</ID>
+    <Prefix>logs</Prefix>
+    <Status>Enabled</Status>
+    <Filter>
+      <Not>
+        <Prefix>logs1</Prefix>
+        <Tag><Key>key1</Key><Value>value1</Value></Tag>
+      </Not>
+    </Filter>
+    <Transition>
+      <Days>30</Days>
+      <StorageClass>Archive</StorageClass>
+    </Transition>
+    <Expiration>
+      <Days>100</Days>
+    </Expiration>
+  </Rule>
+  <Rule>
+    <ID>tagged</ID>
+    <Prefix>data/</Prefix>
+    <Status>Disabled</Status>
+    <Tag><Key>key1</Key><Value>value1</Value></Tag>
+    <Tag><Key>key2</Key><Value>value2</Value></Tag>
+    <Expiration>
+      <CreatedBeforeDate>2030-01-01T00:00:00.000Z</CreatedBeforeDate>
+    </Expiration>
+    <AbortMultipartUpload>
+      <CreatedBeforeDate>2030-01-01T00:00:00.000Z</CreatedBeforeDate>
+    </AbortMultipartUpload>
+  </Rule>
+</LifecycleConfiguration>"#;
+
+    const ACCESS_TIME_RESPONSE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<LifecycleConfiguration>
+  <Rule>
+    <ID>atime transition1</ID>
+    <Prefix>logs1/</Prefix>
+    <Status>Enabled</Status>
+    <Transition>
+      <Days>30</Days>
+      <StorageClass>IA</StorageClass>
+      <IsAccessTime>true</IsAccessTime>
+      <ReturnToStdWhenVisit>false</ReturnToStdWhenVisit>
+    </Transition>
+    <Transition>
+      <Days>180</Days>
+      <StorageClass>Archive</StorageClass>
+      <IsAccessTime>true</IsAccessTime>
+      <ReturnToStdWhenVisit>false</ReturnToStdWhenVisit>
+      <AllowSmallFile>true</AllowSmallFile>
+    </Transition>
+    <AtimeBase>1631698332</AtimeBase>
+  </Rule>
+  <Rule>
+    <ID>versions</ID>
+    <Prefix>logs2/</Prefix>
+    <Status>Enabled</Status>
+    <Expiration>
+      <ExpiredObjectDeleteMarker>true</ExpiredObjectDeleteMarker>
+    </Expiration>
+    <NoncurrentVersionExpiration>
+      <NoncurrentDays>20</NoncurrentDays>
+    </NoncurrentVersionExpiration>
+    <NoncurrentVersionTransition>
+      <NoncurrentDays>10</NoncurrentDays>
+      <StorageClass>IA</StorageClass>
+      <IsAccessTime>true</IsAccessTime>
+      <ReturnToStdWhenVisit>true</ReturnToStdWhenVisit>
+    </NoncurrentVersionTransition>
+    <AtimeBase>1631698332</AtimeBase>
+  </Rule>
+</LifecycleConfiguration>"#;
+
+    fn round_trip(config: &LifecycleConfiguration) {
+        let xml = config.to_xml().unwrap();
+        assert_eq!(&LifecycleConfiguration::from_xml(&xml).unwrap(), config);
+    }
+
+    #[test]
+    fn filter_tags_and_dates() {
+        let config = LifecycleConfiguration::from_xml(FILTER_RESPONSE).unwrap();
+        let rules = config.rules();
+        assert_eq!(rules.len(), 2);
+
+        assert_eq!(
+            rules[0].filter.as_ref().unwrap().not,
+            vec![LifecycleNot {
+                prefix: "logs1".to_string(),
+                tag: Some(Tag {
+                    key: "key1".to_string(),
+                    value: "value1".to_string(),
+                }),
+            }]
+        );
+        assert_eq!(rules[0].transition[0].storage_class, StorageClass::Archive);
+        assert_eq!(rules[0].expiration.as_ref().unwrap().days, Some(100));
+
+        assert_eq!(rules[1].status, RuleStatus::Disabled);
+        assert_eq!(rules[1].tag.len(), 2);
+        assert_eq!(rules[1].tag[1].value, "value2");
+        assert_eq!(
+            rules[1].abort_multipart_upload,
+            Some(AbortMultipartUpload {
+                days: None,
+                created_before_date: Some("2030-01-01T00:00:00.000Z".to_string()),
+            })
+        );
+
+        let xml = config.to_xml().unwrap();
+        assert!(
+            xml.contains(
+                "<Filter><Not><Prefix>logs1</Prefix>\
+                 <Tag><Key>key1</Key><Value>value1</Value></Tag></Not></Filter>"
+            ),
+            "{}",
+            xml
+        );
+        round_trip(&config);
+    }
+
+    #[test]
+    fn access_time_and_versions() {
+        let config = LifecycleConfiguration::from_xml(ACCESS_TIME_RESPONSE).unwrap();
+        let rules = config.rules();
+
+        assert_eq!(rules[0].atime_base, Some(1631698332));
+        assert_eq!(
+            rules[0].transition,
+            vec![
+                Transition {
+                    days: Some(30),
+                    is_access_time: Some(true),
+                    return_to_std_when_visit: Some(false),
+                    ..Transition::new(StorageClass::IA)
+                },
+                Transition {
+                    days: Some(180),
+                    is_access_time: Some(true),
+                    return_to_std_when_visit: Some(false),
+                    allow_small_file: Some(true),
+                    ..Transition::new(StorageClass::Archive)
+                },
+            ]
+        );
+
+        let expiration = rules[1].expiration.as_ref().unwrap();
+        assert_eq!(expiration.days, None);
+        assert_eq!(expiration.expired_object_delete_marker, Some(true));
+        assert_eq!(
+            rules[1].noncurrent_version_expiration,
+            Some(NoncurrentVersionExpiration {
+                noncurrent_days: 20
+            })
+        );
+        assert_eq!(
+            rules[1].noncurrent_version_transition,
+            vec![NoncurrentVersionTransition {
+                noncurrent_days: 10,
+                storage_class: StorageClass::IA,
+                is_access_time: Some(true),
+                return_to_std_when_visit: Some(true),
+            }]
+        );
+        round_trip(&config);
+    }
+}
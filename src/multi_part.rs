@@ -1,4 +1,35 @@
+use std::collections::HashMap;
+#[cfg(feature = "fs")]
+use std::io::SeekFrom;
+#[cfg(feature = "fs")]
+use std::path::{Path, PathBuf};
+
+use bytes::Bytes;
+#[cfg(feature = "fs")]
+use futures_util::StreamExt;
+use futures_util::{stream, TryStreamExt};
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "fs")]
+use tokio::io::AsyncSeekExt;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::async_object::AsyncObjectAPI;
+#[cfg(feature = "fs")]
+use crate::checkpoint::{self, FileFingerprint, UploadCheckpoint};
+#[cfg(feature = "fs")]
+use crate::errors::ServiceErrorCode;
+use crate::errors::{Error, ObjectError};
+use crate::oss::OSS;
+use crate::retry::RetryPolicy;
+
+/// Maximum number of parts of a multipart upload.
+pub const MAX_PARTS: u64 = 10_000;
+/// Minimum size of every part but the last one.
+pub const MIN_PART_SIZE: u64 = 100 * 1024;
+/// Maximum size of a part.
+pub const MAX_PART_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+/// Part size used when none is set and the total size allows it.
+pub const DEFAULT_PART_SIZE: u64 = 8 * 1024 * 1024;
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "PascalCase")]
@@ -15,4 +46,887 @@ pub struct CompleteMultipartUploadResult {
     pub bucket: String,
     pub key: String,
     pub e_tag: String,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListPartsResult {
+    pub bucket: String,
+    pub key: String,
+    pub upload_id: String,
+    #[serde(default)]
+    pub next_part_number_marker: String,
+    #[serde(default)]
+    pub is_truncated: bool,
+    #[serde(default)]
+    pub part: Vec<UploadedPart>,
+}
+
+/// A part listed by `list_parts`.
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct UploadedPart {
+    pub part_number: usize,
+    pub last_modified: String,
+    pub e_tag: String,
+    pub size: u64,
+}
+
+/// An uploaded part, as listed in the `CompleteMultipartUpload` body.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct Part {
+    pub part_number: usize,
+    pub e_tag: String,
+}
+
+/// Body of the request completing a multipart upload.
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "PascalCase")]
+pub struct CompleteMultipartUpload {
+    pub part: Vec<Part>,
+}
+
+impl CompleteMultipartUpload {
+    /// Build the body from uploaded parts, in any order.
+    pub fn new(mut parts: Vec<Part>) -> Self {
+        parts.sort_by_key(|p| p.part_number);
+        CompleteMultipartUpload { part: parts }
+    }
+
+    pub fn to_xml(&self) -> Result<String, Error> {
+        Ok(quick_xml::se::to_string_with_root(
+            "CompleteMultipartUpload",
+            self,
+        )?)
+    }
+}
+
+/// Size of the parts of an upload of `total` bytes, `preferred` raised so that
+/// the upload fits in [`MAX_PARTS`] parts.
+pub fn part_size_for(total: u64, preferred: u64) -> u64 {
+    preferred
+        .max(MIN_PART_SIZE)
+        .max(total.div_ceil(MAX_PARTS))
+        .min(MAX_PART_SIZE)
+}
+
+/// Uploads a file or a stream with a multipart upload, several parts at a time.
+///
+/// Every part is retried with the retry policy of the client, or the default
+/// policy when the client has none. When the upload fails, or its future is
+/// dropped before it completes, the multipart upload is aborted so that the
+/// uploaded parts are not kept around.
+///
+/// With a `checkpoint` file, `upload_file` (`fs` feature) saves its progress
+/// instead, and picks up where it stopped when called again after a failure
+/// or a crash.
+///
+/// # Examples
+///
+/// ```no_run
+/// # #[cfg(feature = "fs")]
+/// # async fn demo(oss: oss_rust_sdk::oss::OSS<'_>) -> Result<(), oss_rust_sdk::errors::Error> {
+/// use oss_rust_sdk::multi_part::MultipartUploader;
+///
+/// MultipartUploader::new(&oss)
+///     .concurrency(8)
+///     .upload_file("/tmp/big_file", "object")
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MultipartUploader {
+    oss: OSS<'static>,
+    part_size: Option<u64>,
+    concurrency: usize,
+    headers: HashMap<String, String>,
+    #[cfg(feature = "fs")]
+    checkpoint: Option<PathBuf>,
+}
+
+impl MultipartUploader {
+    pub fn new(oss: &OSS) -> Self {
+        let mut oss = oss.to_owned_client();
+        if oss.retry_policy().is_none() {
+            oss.set_retry_policy(Some(RetryPolicy::default()));
+        }
+        MultipartUploader {
+            oss,
+            part_size: None,
+            concurrency: 4,
+            headers: HashMap::new(),
+            #[cfg(feature = "fs")]
+            checkpoint: None,
+        }
+    }
+
+    /// Preferred part size, raised when needed to stay under [`MAX_PARTS`].
+    pub fn part_size(mut self, part_size: u64) -> Self {
+        self.part_size = Some(part_size);
+        self
+    }
+
+    /// Number of parts uploaded at the same time, 4 by default.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Retry policy of every part, instead of the one of the client.
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.oss.set_retry_policy(Some(retry_policy));
+        self
+    }
+
+    /// Header sent when the upload is initiated, e.g. `Content-Type`.
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    /// Save the progress of `upload_file` in `path`, to resume it later.
+    ///
+    /// The checkpoint is reused when it was saved for the same bucket, object
+    /// and file, with the same size and modification time, and its parts are
+    /// checked against the ones listed by OSS. Failed uploads are not aborted,
+    /// and the checkpoint is removed once the upload completes.
+    #[cfg(feature = "fs")]
+    pub fn checkpoint<P>(mut self, path: P) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.checkpoint = Some(path.into());
+        self
+    }
+
+    #[cfg(feature = "fs")]
+    pub async fn upload_file<P, S>(
+        &self,
+        file: P,
+        object_name: S,
+    ) -> Result<CompleteMultipartUploadResult, Error>
+    where
+        P: AsRef<Path>,
+        S: AsRef<str>,
+    {
+        if let Some(checkpoint) = &self.checkpoint {
+            return self
+                .upload_file_resumable(file.as_ref(), object_name.as_ref(), checkpoint)
+                .await;
+        }
+        let file = tokio::fs::File::open(file).await?;
+        let len = file.metadata().await?.len();
+        self.upload_reader(file, Some(len), object_name).await
+    }
+
+    /// Upload everything read from `reader`. When `len` is unknown the parts
+    /// have the preferred size, and the upload fails past [`MAX_PARTS`] parts.
+    ///
+    /// At most `concurrency` parts are held in memory.
+    pub async fn upload_reader<Rd, S>(
+        &self,
+        reader: Rd,
+        len: Option<u64>,
+        object_name: S,
+    ) -> Result<CompleteMultipartUploadResult, Error>
+    where
+        Rd: AsyncRead + Unpin,
+        S: AsRef<str>,
+    {
+        let preferred = self.part_size.unwrap_or(DEFAULT_PART_SIZE);
+        let part_size = match len {
+            Some(len) => part_size_for(len, preferred),
+            None => part_size_for(0, preferred),
+        };
+        let object_name = object_name.as_ref();
+
+        let init = self
+            .oss
+            .init_multi(
+                object_name,
+                Some(self.headers.clone()),
+                upload_resources(None),
+            )
+            .await?;
+        let mut guard = AbortGuard {
+            oss: Some(self.oss.clone()),
+            object_name: object_name.to_string(),
+            upload_id: init.upload_id,
+        };
+
+        let result = self
+            .upload_and_complete(reader, part_size, object_name, &guard.upload_id)
+            .await;
+        match result {
+            Ok(result) => {
+                guard.oss = None;
+                Ok(result)
+            }
+            Err(e) => {
+                guard.abort().await;
+                Err(e)
+            }
+        }
+    }
+
+    async fn upload_and_complete<Rd>(
+        &self,
+        reader: Rd,
+        part_size: u64,
+        object_name: &str,
+        upload_id: &str,
+    ) -> Result<CompleteMultipartUploadResult, Error>
+    where
+        Rd: AsyncRead + Unpin,
+    {
+        let parts = self
+            .upload_parts(reader, part_size, object_name, upload_id)
+            .await?;
+        let body = CompleteMultipartUpload::new(parts).to_xml()?;
+        self.oss
+            .complete_multi(
+                body,
+                object_name,
+                None,
+                upload_resources(Some((upload_id, None))),
+            )
+            .await
+    }
+
+    async fn upload_parts<Rd>(
+        &self,
+        reader: Rd,
+        part_size: u64,
+        object_name: &str,
+        upload_id: &str,
+    ) -> Result<Vec<Part>, Error>
+    where
+        Rd: AsyncRead + Unpin,
+    {
+        let chunks = stream::try_unfold((reader, 1usize), move |(mut reader, number)| async move {
+            let mut buf = Vec::new();
+            (&mut reader).take(part_size).read_to_end(&mut buf).await?;
+            // an empty object still needs a part, later empty reads are the end
+            if buf.is_empty() && number > 1 {
+                return Ok(None);
+            }
+            if number as u64 > MAX_PARTS {
+                return Err(Error::Object(ObjectError::PutError {
+                    msg: format!("more than {} parts of {} bytes", MAX_PARTS, part_size),
+                }));
+            }
+            Ok(Some(((number, Bytes::from(buf)), (reader, number + 1))))
+        });
+
+        chunks
+            .map_ok(|(part_number, buf)| async move {
+                let e_tag = self
+                    .oss
+                    .upload_part(
+                        buf,
+                        object_name,
+                        None,
+                        upload_resources(Some((upload_id, Some(part_number)))),
+                    )
+                    .await?;
+                Ok(Part { part_number, e_tag })
+            })
+            .try_buffer_unordered(self.concurrency)
+            .try_collect()
+            .await
+    }
+}
+
+#[cfg(feature = "fs")]
+impl MultipartUploader {
+    async fn upload_file_resumable(
+        &self,
+        file: &Path,
+        object_name: &str,
+        checkpoint_path: &Path,
+    ) -> Result<CompleteMultipartUploadResult, Error> {
+        let fingerprint = FileFingerprint::of(file).await?;
+        let mut checkpoint = match self
+            .load_checkpoint(checkpoint_path, object_name, &fingerprint)
+            .await?
+        {
+            Some(checkpoint) => checkpoint,
+            None => {
+                let init = self
+                    .oss
+                    .init_multi(
+                        object_name,
+                        Some(self.headers.clone()),
+                        upload_resources(None),
+                    )
+                    .await?;
+                let preferred = self.part_size.unwrap_or(DEFAULT_PART_SIZE);
+                let checkpoint = UploadCheckpoint {
+                    bucket: self.oss.bucket().to_string(),
+                    object_name: object_name.to_string(),
+                    file: fingerprint,
+                    upload_id: init.upload_id,
+                    part_size: part_size_for(fingerprint.size, preferred),
+                    parts: Vec::new(),
+                };
+                checkpoint::save(checkpoint_path, &checkpoint).await?;
+                checkpoint
+            }
+        };
+
+        let upload_id = checkpoint.upload_id.clone();
+        let part_size = checkpoint.part_size;
+        let missing: Vec<usize> = (1..=part_count(fingerprint.size, part_size))
+            .filter(|n| !checkpoint.parts.iter().any(|p| p.part_number == *n))
+            .collect();
+        let mut uploads = std::pin::pin!(stream::iter(missing)
+            .map(|part_number| {
+                let upload_id = &upload_id;
+                async move {
+                    let offset = (part_number as u64 - 1) * part_size;
+                    let buf = read_part(file, offset, part_size).await?;
+                    let e_tag = self
+                        .oss
+                        .upload_part(
+                            buf,
+                            object_name,
+                            None,
+                            upload_resources(Some((upload_id, Some(part_number)))),
+                        )
+                        .await?;
+                    Ok::<_, Error>(Part { part_number, e_tag })
+                }
+            })
+            .buffer_unordered(self.concurrency));
+        while let Some(part) = uploads.try_next().await? {
+            checkpoint.parts.push(part);
+            checkpoint::save(checkpoint_path, &checkpoint).await?;
+        }
+
+        if FileFingerprint::of(file).await? != fingerprint {
+            return Err(Error::Object(ObjectError::PutError {
+                msg: format!("{} changed during the upload", file.display()),
+            }));
+        }
+        let body = CompleteMultipartUpload::new(checkpoint.parts).to_xml()?;
+        let result = self
+            .oss
+            .complete_multi(
+                body,
+                object_name,
+                None,
+                upload_resources(Some((&upload_id, None))),
+            )
+            .await?;
+        checkpoint::remove(checkpoint_path).await?;
+        Ok(result)
+    }
+
+    /// The saved checkpoint, keeping only the parts still known by OSS.
+    async fn load_checkpoint(
+        &self,
+        path: &Path,
+        object_name: &str,
+        fingerprint: &FileFingerprint,
+    ) -> Result<Option<UploadCheckpoint>, Error> {
+        let mut checkpoint: UploadCheckpoint = match checkpoint::load(path).await? {
+            Some(checkpoint) => checkpoint,
+            None => return Ok(None),
+        };
+        if !checkpoint.matches(self.oss.bucket(), object_name, fingerprint) {
+            warn!(
+                "checkpoint {} is for another upload, start over",
+                path.display()
+            );
+            if checkpoint.bucket == self.oss.bucket() {
+                abort(
+                    self.oss.clone(),
+                    &checkpoint.object_name,
+                    &checkpoint.upload_id,
+                )
+                .await;
+            }
+            return Ok(None);
+        }
+
+        let listed = match self
+            .list_all_parts(object_name, &checkpoint.upload_id)
+            .await
+        {
+            Ok(listed) => listed,
+            Err(e) if matches!(e.service_error_code(), Some(ServiceErrorCode::NoSuchUpload)) => {
+                warn!(
+                    "upload of checkpoint {} is gone, start over",
+                    path.display()
+                );
+                return Ok(None);
+            }
+            Err(e) => return Err(e),
+        };
+        let part_size = checkpoint.part_size;
+        checkpoint.parts.retain(|part| {
+            let offset = (part.part_number as u64).saturating_sub(1) * part_size;
+            let expected = part_size.min(fingerprint.size.saturating_sub(offset));
+            listed.iter().any(|l| {
+                l.part_number == part.part_number
+                    && l.size == expected
+                    && l.e_tag.trim_matches('"') == part.e_tag.trim_matches('"')
+            })
+        });
+        Ok(Some(checkpoint))
+    }
+
+    async fn list_all_parts(
+        &self,
+        object_name: &str,
+        upload_id: &str,
+    ) -> Result<Vec<UploadedPart>, Error> {
+        let mut parts = Vec::new();
+        let mut marker = String::new();
+        loop {
+            let mut resources = upload_resources(Some((upload_id, None)));
+            resources.insert("max-parts".to_string(), Some("1000".to_string()));
+            if !marker.is_empty() {
+                resources.insert("part-number-marker".to_string(), Some(marker));
+            }
+            let result = self
+                .oss
+                .list_parts(object_name, None, Some(resources))
+                .await?;
+            parts.extend(result.part);
+            if !result.is_truncated || result.next_part_number_marker.is_empty() {
+                return Ok(parts);
+            }
+            marker = result.next_part_number_marker;
+        }
+    }
+}
+
+/// Number of parts of a file, an empty one is uploaded as a single empty part.
+#[cfg(feature = "fs")]
+fn part_count(size: u64, part_size: u64) -> usize {
+    size.div_ceil(part_size).max(1) as usize
+}
+
+#[cfg(feature = "fs")]
+async fn read_part(file: &Path, offset: u64, part_size: u64) -> Result<Vec<u8>, Error> {
+    let mut file = tokio::fs::File::open(file).await?;
+    file.seek(SeekFrom::Start(offset)).await?;
+    let mut buf = Vec::new();
+    file.take(part_size).read_to_end(&mut buf).await?;
+    Ok(buf)
+}
+
+fn upload_resources(upload: Option<(&str, Option<usize>)>) -> HashMap<String, Option<String>> {
+    let mut resources = HashMap::new();
+    match upload {
+        None => {
+            resources.insert("uploads".to_string(), None);
+        }
+        Some((upload_id, part_number)) => {
+            resources.insert("uploadId".to_string(), Some(upload_id.to_string()));
+            if let Some(part_number) = part_number {
+                resources.insert("partNumber".to_string(), Some(part_number.to_string()));
+            }
+        }
+    }
+    resources
+}
+
+/// Aborts the multipart upload when dropped while still holding the client.
+struct AbortGuard {
+    oss: Option<OSS<'static>>,
+    object_name: String,
+    upload_id: String,
+}
+
+impl AbortGuard {
+    async fn abort(&mut self) {
+        if let Some(oss) = self.oss.take() {
+            abort(oss, &self.object_name, &self.upload_id).await;
+        }
+    }
+}
+
+impl Drop for AbortGuard {
+    fn drop(&mut self) {
+        let oss = match self.oss.take() {
+            Some(oss) => oss,
+            None => return,
+        };
+        let object_name = std::mem::take(&mut self.object_name);
+        let upload_id = std::mem::take(&mut self.upload_id);
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move { abort(oss, &object_name, &upload_id).await });
+            }
+            Err(_) => warn!(
+                "multipart upload {} of {} dropped outside of a runtime, not aborted",
+                upload_id, object_name
+            ),
+        }
+    }
+}
+
+async fn abort(oss: OSS<'static>, object_name: &str, upload_id: &str) {
+    if let Err(e) = oss
+        .abort_multi(object_name, None, upload_resources(Some((upload_id, None))))
+        .await
+    {
+        warn!(
+            "abort multipart upload {} of {}: {}",
+            upload_id, object_name, e
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{self, Received, Response};
+    use std::collections::BTreeMap;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    const UPLOAD_ID: &str = "0004B9895DBBB6EC98E36";
+
+    #[derive(Default)]
+    struct Upload {
+        requests: Vec<Received>,
+        /// Data of the uploaded parts, by number.
+        parts: BTreeMap<usize, Vec<u8>>,
+        /// Body of CompleteMultipartUpload.
+        complete: Option<String>,
+    }
+
+    impl Upload {
+        /// Part numbers of the UploadPart requests, in the order received.
+        fn part_requests(&self) -> Vec<usize> {
+            self.requests
+                .iter()
+                .filter(|r| r.method == "PUT")
+                .map(|r| r.query("partNumber").unwrap().parse().unwrap())
+                .collect()
+        }
+
+        fn aborted(&self) -> bool {
+            self.requests
+                .iter()
+                .any(|r| r.method == "DELETE" && r.query("uploadId").is_some())
+        }
+    }
+
+    fn e_tag(part_number: usize) -> String {
+        format!("\"etag-{}\"", part_number)
+    }
+
+    /// Stand-in of the multipart upload requests. The UploadPart requests of
+    /// the part numbers in `failures` fail once each with the status and code,
+    /// and ListParts lists the `listed` part numbers and sizes.
+    fn multipart_server(
+        failures: Vec<(usize, u16, &'static str)>,
+        listed: Vec<(usize, u64)>,
+    ) -> (OSS<'static>, Arc<Mutex<Upload>>) {
+        let upload = Arc::new(Mutex::new(Upload::default()));
+        let state = upload.clone();
+        let mut failures = failures;
+        let url = test_server::serve(move |request| {
+            let mut upload = state.lock().unwrap();
+            upload.requests.push(request.clone());
+            match (request.method.as_str(), request.query("partNumber")) {
+                ("POST", _) if request.query("uploads").is_some() => Response::ok(format!(
+                    "<InitiateMultipartUploadResult><Bucket>bucket</Bucket>\
+                     <Key>object</Key><UploadId>{}</UploadId></InitiateMultipartUploadResult>",
+                    UPLOAD_ID
+                )),
+                ("PUT", Some(number)) => {
+                    let number: usize = number.parse().unwrap();
+                    if let Some(i) = failures.iter().position(|f| f.0 == number) {
+                        let (_, status, code) = failures.remove(i);
+                        return Response::error(status, code);
+                    }
+                    upload.parts.insert(number, request.body);
+                    Response::ok("").header("ETag", e_tag(number))
+                }
+                ("GET", _) => {
+                    let parts: String = listed
+                        .iter()
+                        .map(|(number, size)| {
+                            format!(
+                                "<Part><PartNumber>{}</PartNumber>\
+                                 <LastModified>2012-02-23T07:01:34.000Z</LastModified>\
+                                 <ETag>{}</ETag><Size>{}</Size></Part>",
+                                number,
+                                e_tag(*number),
+                                size
+                            )
+                        })
+                        .collect();
+                    Response::ok(format!(
+                        "<ListPartsResult><Bucket>bucket</Bucket><Key>object</Key>\
+                         <UploadId>{}</UploadId><IsTruncated>false</IsTruncated>{}\
+                         </ListPartsResult>",
+                        UPLOAD_ID, parts
+                    ))
+                }
+                ("POST", _) => {
+                    upload.complete = Some(request.body_str());
+                    Response::ok(
+                        "<CompleteMultipartUploadResult>\
+                         <Location>http://bucket.oss-cn-hangzhou.aliyuncs.com/object</Location>\
+                         <Bucket>bucket</Bucket><Key>object</Key><ETag>\"B864DB6A936D376F9F8D3ED3BBE540DD-3\"</ETag>\
+                         </CompleteMultipartUploadResult>",
+                    )
+                }
+                ("DELETE", _) => Response::status(204),
+                _ => Response::error(400, "InvalidArgument"),
+            }
+        });
+        (test_server::client(&url), upload)
+    }
+
+    /// Data of `parts` parts of `MIN_PART_SIZE`, the last one shorter.
+    fn data(parts: u64) -> Vec<u8> {
+        let len = (parts - 1) * MIN_PART_SIZE + 1000;
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn assert_completed(upload: &Upload, data: &[u8], parts: usize) {
+        assert_eq!(upload.parts.len(), parts);
+        assert_eq!(
+            upload.parts.values().flatten().copied().collect::<Vec<_>>(),
+            data
+        );
+
+        let body = upload.complete.as_ref().unwrap();
+        assert!(
+            body.starts_with("<CompleteMultipartUpload><Part><PartNumber>1</PartNumber>"),
+            "{}",
+            body
+        );
+        let complete: CompleteMultipartUpload = quick_xml::de::from_str(body).unwrap();
+        let expected: Vec<Part> = (1..=parts)
+            .map(|part_number| Part {
+                part_number,
+                e_tag: e_tag(part_number),
+            })
+            .collect();
+        assert_eq!(complete.part, expected);
+    }
+
+    #[test]
+    fn part_size_stays_within_the_limits() {
+        assert_eq!(part_size_for(0, 1), MIN_PART_SIZE);
+        assert_eq!(part_size_for(0, DEFAULT_PART_SIZE), DEFAULT_PART_SIZE);
+        assert_eq!(
+            part_size_for(MIN_PART_SIZE * MAX_PARTS, MIN_PART_SIZE),
+            MIN_PART_SIZE
+        );
+        assert_eq!(
+            part_size_for(MIN_PART_SIZE * MAX_PARTS + 1, MIN_PART_SIZE),
+            MIN_PART_SIZE + 1
+        );
+        assert_eq!(
+            part_size_for(MAX_PART_SIZE * MAX_PARTS, DEFAULT_PART_SIZE),
+            MAX_PART_SIZE
+        );
+        assert_eq!(part_size_for(0, MAX_PART_SIZE + 1), MAX_PART_SIZE);
+        assert_eq!(
+            part_size_for(MAX_PART_SIZE * MAX_PARTS + 1, DEFAULT_PART_SIZE),
+            MAX_PART_SIZE
+        );
+
+        for total in [
+            1,
+            DEFAULT_PART_SIZE * MAX_PARTS - 1,
+            DEFAULT_PART_SIZE * MAX_PARTS + 1,
+            3 * 1024 * 1024 * 1024 * 1024 + 7,
+            MAX_PART_SIZE * MAX_PARTS,
+        ] {
+            let part_size = part_size_for(total, DEFAULT_PART_SIZE);
+            assert!(total.div_ceil(part_size) <= MAX_PARTS, "{}", total);
+        }
+    }
+
+    #[tokio::test]
+    async fn uploads_the_parts_and_retries_them() {
+        let (oss, upload) = multipart_server(vec![(2, 503, "ServiceUnavailable")], Vec::new());
+        let data = data(3);
+
+        let result = MultipartUploader::new(&oss)
+            .part_size(MIN_PART_SIZE)
+            .concurrency(2)
+            .upload_reader(&data[..], Some(data.len() as u64), "object")
+            .await
+            .unwrap();
+
+        assert_eq!(result.key, "object");
+        let upload = upload.lock().unwrap();
+        assert_completed(&upload, &data, 3);
+        let mut part_requests = upload.part_requests();
+        part_requests.sort();
+        assert_eq!(part_requests, [1, 2, 2, 3]);
+        assert!(!upload.aborted());
+    }
+
+    #[tokio::test]
+    async fn failed_upload_is_aborted() {
+        let (oss, upload) = multipart_server(vec![(2, 403, "AccessDenied")], Vec::new());
+        let data = data(3);
+
+        let err = MultipartUploader::new(&oss)
+            .part_size(MIN_PART_SIZE)
+            .upload_reader(&data[..], None, "object")
+            .await
+            .unwrap_err();
+
+        assert_eq!(
+            err.service_error_code(),
+            Some(crate::errors::ServiceErrorCode::AccessDenied)
+        );
+        let upload = upload.lock().unwrap();
+        assert!(upload.complete.is_none());
+        let abort = upload.requests.last().unwrap();
+        assert_eq!(abort.method, "DELETE");
+        assert_eq!(abort.query("uploadId"), Some(UPLOAD_ID));
+    }
+
+    #[tokio::test]
+    async fn dropped_upload_is_aborted() {
+        let (oss, upload) = multipart_server(Vec::new(), Vec::new());
+        // a reader which never ends, the upload is still reading its first part
+        let (_writer, reader) = tokio::io::duplex(16);
+
+        let uploader = MultipartUploader::new(&oss);
+        let upload_future = uploader.upload_reader(reader, None, "object");
+        assert!(
+            tokio::time::timeout(Duration::from_millis(200), upload_future)
+                .await
+                .is_err()
+        );
+
+        for _ in 0..100 {
+            if upload.lock().unwrap().aborted() {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("the dropped upload was not aborted");
+    }
+
+    /// `data` written to a file in a fresh directory, and the path of the
+    /// checkpoint of its upload.
+    #[cfg(feature = "fs")]
+    fn file(name: &str, data: &[u8]) -> (PathBuf, PathBuf) {
+        let dir = std::env::temp_dir().join(format!(
+            "oss-rust-sdk-upload-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("object");
+        std::fs::write(&file, data).unwrap();
+        (file, dir.join("object.checkpoint"))
+    }
+
+    #[cfg(feature = "fs")]
+    fn parts(numbers: impl IntoIterator<Item = usize>) -> Vec<Part> {
+        numbers
+            .into_iter()
+            .map(|part_number| Part {
+                part_number,
+                e_tag: e_tag(part_number),
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "fs")]
+    #[tokio::test]
+    async fn resumes_the_parts_missing_from_the_upload() {
+        let data = data(4);
+        // part 2 is gone and part 3 was cut short, only part 1 is kept
+        let (oss, upload) =
+            multipart_server(Vec::new(), vec![(1, MIN_PART_SIZE), (3, MIN_PART_SIZE - 1)]);
+        let (file, checkpoint_path) = file("resume", &data);
+        let checkpoint = UploadCheckpoint {
+            bucket: "bucket".to_string(),
+            object_name: "object".to_string(),
+            file: FileFingerprint::of(&file).await.unwrap(),
+            upload_id: UPLOAD_ID.to_string(),
+            part_size: MIN_PART_SIZE,
+            parts: parts([3, 1, 2]),
+        };
+        checkpoint::save(&checkpoint_path, &checkpoint)
+            .await
+            .unwrap();
+
+        MultipartUploader::new(&oss)
+            .part_size(DEFAULT_PART_SIZE)
+            .checkpoint(&checkpoint_path)
+            .upload_file(&file, "object")
+            .await
+            .unwrap();
+
+        let upload = upload.lock().unwrap();
+        assert!(upload.requests.iter().all(|r| r.query("uploads").is_none()));
+        assert_eq!(upload.requests[0].method, "GET");
+        let mut part_requests = upload.part_requests();
+        part_requests.sort();
+        assert_eq!(part_requests, [2, 3, 4]);
+        let part_size = MIN_PART_SIZE as usize;
+        for (number, part) in &upload.parts {
+            let start = (number - 1) * part_size;
+            let end = (start + part_size).min(data.len());
+            assert_eq!(part, &data[start..end], "part {}", number);
+        }
+
+        let complete: CompleteMultipartUpload =
+            quick_xml::de::from_str(upload.complete.as_ref().unwrap()).unwrap();
+        assert_eq!(complete.part, parts(1..=4));
+        assert!(!checkpoint_path.exists());
+        assert!(!upload.aborted());
+    }
+
+    #[cfg(feature = "fs")]
+    #[tokio::test]
+    async fn changed_file_starts_a_new_upload() {
+        let data = data(2);
+        let (oss, upload) = multipart_server(Vec::new(), Vec::new());
+        let (file, checkpoint_path) = file("changed", &data);
+        let mut fingerprint = FileFingerprint::of(&file).await.unwrap();
+        fingerprint.size += 1;
+        let checkpoint = UploadCheckpoint {
+            bucket: "bucket".to_string(),
+            object_name: "object".to_string(),
+            file: fingerprint,
+            upload_id: "stale-upload".to_string(),
+            part_size: MIN_PART_SIZE,
+            parts: parts([1]),
+        };
+        checkpoint::save(&checkpoint_path, &checkpoint)
+            .await
+            .unwrap();
+
+        MultipartUploader::new(&oss)
+            .part_size(MIN_PART_SIZE)
+            .checkpoint(&checkpoint_path)
+            .upload_file(&file, "object")
+            .await
+            .unwrap();
+
+        let upload = upload.lock().unwrap();
+        let stale = &upload.requests[0];
+        assert_eq!(stale.method, "DELETE");
+        assert_eq!(stale.query("uploadId"), Some("stale-upload"));
+        assert!(upload.requests[1].query("uploads").is_some());
+        assert_completed(&upload, &data, 2);
+        assert!(!checkpoint_path.exists());
+    }
+}
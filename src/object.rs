@@ -1,19 +1,35 @@
+use bytes::Bytes;
+use chrono::Utc;
 use quick_xml::{events::Event, Reader};
 use reqwest::header::{HeaderMap, HeaderValue, DATE};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
-
-use crate::auth::Auth;
-use crate::oss::RequestType;
-
-use super::errors::{Error, ObjectError};
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::auth::{get_v4_credential, Auth, OSS4_ALGORITHM};
+use crate::credentials::Credentials;
+use crate::errors::ObjectError;
+use crate::multi_part::{
+    CompleteMultipartUploadResult, InitiateMultipartUploadResult, ListPartsResult,
+};
+use crate::options::{CopyObjectOptions, GetObjectOptions, ObjectOptions, PutObjectOptions};
+use crate::oss::{ObjectMeta, RequestType, SignatureVersion};
+use crate::request::Request;
+use crate::restore::{restore_object_request, RestoreRequest};
+use crate::tagging::{tagging_request, Tagging};
+use crate::utils::content_md5;
+use crate::versioning::{version_id, DeleteObjectResult, ListObjectVersions};
+
+use super::errors::Error;
 use super::oss::OSS;
-use super::utils::*;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct CommonPrefix {
-    prefix: String,
+    pub(crate) prefix: String,
 }
 
 impl CommonPrefix {
@@ -33,6 +49,8 @@ pub struct ListObjects {
     delimiter: String,
     prefix: String,
     marker: String,
+    #[serde(default)]
+    next_marker: String,
     max_keys: String,
     is_truncated: bool,
 
@@ -43,6 +61,7 @@ pub struct ListObjects {
 }
 
 impl ListObjects {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         name: String,
         delimiter: String,
@@ -59,6 +78,7 @@ impl ListObjects {
             delimiter,
             prefix,
             marker,
+            next_marker: String::new(),
             max_keys,
             is_truncated,
 
@@ -83,14 +103,121 @@ impl ListObjects {
         &self.marker
     }
 
+    /// Marker of the next page, set when the listing is truncated and a
+    /// delimiter was given.
+    pub fn next_marker(&self) -> &str {
+        &self.next_marker
+    }
+
+    pub fn max_keys(&self) -> &str {
+        &self.max_keys
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.is_truncated
+    }
+
+    pub fn contents(&self) -> &Vec<Object> {
+        &self.contents
+    }
+
+    pub fn common_prefixes(&self) -> &Vec<CommonPrefix> {
+        &self.common_prefixes
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListObjectsV2 {
+    name: String,
+    #[serde(default)]
+    prefix: String,
+    #[serde(default)]
+    delimiter: String,
+    #[serde(default)]
+    start_after: String,
+    #[serde(default)]
+    encoding_type: String,
+    max_keys: String,
+    #[serde(default)]
+    key_count: usize,
+    is_truncated: bool,
+    #[serde(default)]
+    continuation_token: String,
+    #[serde(default)]
+    next_continuation_token: String,
+
+    #[serde(default)]
+    contents: Vec<Object>,
+    #[serde(default)]
+    common_prefixes: Vec<CommonPrefix>,
+}
+
+impl ListObjectsV2 {
+    /// Parse a response, decoding the keys and prefixes when the listing was
+    /// requested with `encoding-type=url`.
+    pub(crate) fn from_xml(body: &str) -> Result<Self, Error> {
+        let mut list = quick_xml::de::from_str::<ListObjectsV2>(body)?;
+        if list.encoding_type == "url" {
+            for s in [
+                &mut list.prefix,
+                &mut list.delimiter,
+                &mut list.start_after,
+                &mut list.next_continuation_token,
+            ] {
+                url_decode(s);
+            }
+            for object in list.contents.iter_mut() {
+                url_decode(&mut object.key);
+            }
+            for prefix in list.common_prefixes.iter_mut() {
+                url_decode(&mut prefix.prefix);
+            }
+        }
+        Ok(list)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn delimiter(&self) -> &str {
+        &self.delimiter
+    }
+
+    pub fn start_after(&self) -> &str {
+        &self.start_after
+    }
+
+    pub fn encoding_type(&self) -> &str {
+        &self.encoding_type
+    }
+
     pub fn max_keys(&self) -> &str {
         &self.max_keys
     }
 
+    pub fn key_count(&self) -> usize {
+        self.key_count
+    }
+
     pub fn is_truncated(&self) -> bool {
         self.is_truncated
     }
 
+    pub fn continuation_token(&self) -> &str {
+        &self.continuation_token
+    }
+
+    /// Pass as `continuation-token` to list the next page.
+    pub fn next_continuation_token(&self) -> &str {
+        &self.next_continuation_token
+    }
+
     pub fn contents(&self) -> &Vec<Object> {
         &self.contents
     }
@@ -98,9 +225,394 @@ impl ListObjects {
     pub fn common_prefixes(&self) -> &Vec<CommonPrefix> {
         &self.common_prefixes
     }
+
+    /// Objects and then common prefixes of the page.
+    pub(crate) fn into_entries(self) -> Vec<ListEntry> {
+        self.contents
+            .into_iter()
+            .map(ListEntry::Object)
+            .chain(
+                self.common_prefixes
+                    .into_iter()
+                    .map(ListEntry::CommonPrefix),
+            )
+            .collect()
+    }
+}
+
+#[inline]
+pub(crate) fn url_decode(s: &mut String) {
+    if let Ok(decoded) = urlencoding::decode(s) {
+        *s = decoded.into_owned();
+    }
+}
+
+/// An item of a paginated listing.
+#[derive(Clone, Debug)]
+pub enum ListEntry {
+    Object(Object),
+    CommonPrefix(CommonPrefix),
+}
+
+/// Resources of a ListObjectsV2 request, for the page after `token`.
+pub(crate) fn list_v2_resources(
+    resources: &HashMap<String, Option<String>>,
+    token: Option<&str>,
+) -> HashMap<String, Option<String>> {
+    let mut resources = resources.clone();
+    resources.insert("list-type".to_string(), Some("2".to_string()));
+    if let Some(token) = token {
+        resources.insert("continuation-token".to_string(), Some(token.to_string()));
+    }
+    resources
+}
+
+/// Blocking iterator over every object and common prefix of a listing,
+/// requesting the next page when the current one is exhausted.
+pub struct ListObjectsV2Iter<'a, 'b> {
+    oss: &'b OSS<'a>,
+    headers: HashMap<String, String>,
+    resources: HashMap<String, Option<String>>,
+    token: Option<String>,
+    entries: std::vec::IntoIter<ListEntry>,
+    done: bool,
+}
+
+impl<'a, 'b> Iterator for ListObjectsV2Iter<'a, 'b> {
+    type Item = Result<ListEntry, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(entry) = self.entries.next() {
+                return Some(Ok(entry));
+            }
+            if self.done {
+                return None;
+            }
+            let page = self.oss.list_objects_v2(
+                Some(self.headers.clone()),
+                Some(list_v2_resources(&self.resources, self.token.as_deref())),
+            );
+            let page = match page {
+                Ok(page) => page,
+                Err(e) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+            };
+            self.done = !page.is_truncated() || page.next_continuation_token().is_empty();
+            self.token = Some(page.next_continuation_token().to_string());
+            self.entries = page.into_entries().into_iter();
+        }
+    }
+}
+
+/// Result of `delete_objects`, quiet requests only report the failed keys.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteObjectsResult {
+    #[serde(default)]
+    deleted: Vec<DeletedObject>,
+    #[serde(default, rename = "Error")]
+    failed: Vec<DeleteObjectError>,
+    #[serde(default, skip_serializing)]
+    encoding_type: String,
+}
+
+impl DeleteObjectsResult {
+    /// Parse a response, decoding the keys of a request sent with
+    /// `encoding-type=url`.
+    pub(crate) fn from_xml(body: &str) -> Result<Self, Error> {
+        if body.trim().is_empty() {
+            return Ok(Self::default());
+        }
+        let mut result = quick_xml::de::from_str::<DeleteObjectsResult>(body)?;
+        if result.encoding_type == "url" {
+            for deleted in result.deleted.iter_mut() {
+                url_decode(&mut deleted.key);
+            }
+            for failed in result.failed.iter_mut() {
+                url_decode(&mut failed.key);
+            }
+        }
+        Ok(result)
+    }
+
+    pub(crate) fn extend(&mut self, other: DeleteObjectsResult) {
+        self.deleted.extend(other.deleted);
+        self.failed.extend(other.failed);
+    }
+
+    pub fn deleted(&self) -> &Vec<DeletedObject> {
+        &self.deleted
+    }
+
+    pub fn failed(&self) -> &Vec<DeleteObjectError> {
+        &self.failed
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeletedObject {
+    key: String,
+    #[serde(default)]
+    version_id: String,
+    #[serde(default)]
+    delete_marker: bool,
+    #[serde(default)]
+    delete_marker_version_id: String,
+}
+
+impl DeletedObject {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn version_id(&self) -> &str {
+        &self.version_id
+    }
+
+    pub fn delete_marker(&self) -> bool {
+        self.delete_marker
+    }
+
+    pub fn delete_marker_version_id(&self) -> &str {
+        &self.delete_marker_version_id
+    }
+}
+
+/// Result of `delete_prefix`, which counts the deleted objects rather than
+/// keeping their keys.
+#[derive(Clone, Debug, Default)]
+pub struct DeletePrefixResult {
+    deleted: u64,
+    failed: Vec<DeleteObjectError>,
+}
+
+impl DeletePrefixResult {
+    /// Add a quiet batch of `count` keys.
+    pub(crate) fn add_batch(&mut self, count: usize, batch: DeleteObjectsResult) {
+        self.deleted += (count - batch.failed.len()) as u64;
+        self.failed.extend(batch.failed);
+    }
+
+    /// Number of objects deleted.
+    pub fn deleted(&self) -> u64 {
+        self.deleted
+    }
+
+    pub fn failed(&self) -> &Vec<DeleteObjectError> {
+        &self.failed
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteObjectError {
+    key: String,
+    #[serde(default)]
+    code: String,
+    #[serde(default)]
+    message: String,
+}
+
+impl DeleteObjectError {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+/// The `Grant` of a GetObjectACL response.
+pub(crate) fn parse_object_acl(body: &str) -> Result<String, Error> {
+    let mut reader = Reader::from_str(body);
+    reader.trim_text(true);
+    let mut grant = String::new();
+
+    loop {
+        match reader.read_event()? {
+            Event::Start(ref e) if e.name().as_ref() == b"Grant" => {
+                grant = reader.read_text(e.name())?.to_string();
+            }
+            Event::Eof => break,
+            _ => (),
+        }
+    }
+
+    Ok(grant)
+}
+
+/// The ETag of an UploadPart response, needed to complete the upload.
+pub(crate) fn upload_part_e_tag(headers: &HeaderMap) -> Result<String, Error> {
+    match headers.get("ETag").and_then(|v| v.to_str().ok()) {
+        Some(e_tag) => Ok(e_tag.to_string()),
+        None => Err(Error::Object(ObjectError::PutError {
+            msg: "upload part response without ETag".to_string(),
+        })),
+    }
+}
+
+/// Maximum number of keys of a DeleteMultipleObjects request.
+pub const DELETE_OBJECTS_BATCH: usize = 1000;
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct Delete<'k> {
+    quiet: bool,
+    object: Vec<DeleteKey<'k>>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+struct DeleteKey<'k> {
+    key: Cow<'k, str>,
+}
+
+/// DeleteMultipleObjects request of at most [`DELETE_OBJECTS_BATCH`] keys.
+/// The keys are url encoded, so that keys which can not be written in XML
+/// can be deleted too.
+pub(crate) fn delete_objects_request(keys: &[String], quiet: bool) -> Result<Request, Error> {
+    let body = delete_objects_body(keys, quiet)?;
+    let md5 = content_md5(body.as_bytes());
+
+    Ok(Request::new(
+        RequestType::Post,
+        String::new(),
+        None::<HashMap<String, String>>,
+        None,
+    )
+    .resource("delete", None::<String>)
+    .resource("encoding-type", Some("url"))
+    .header("Content-MD5", md5)
+    .body(body))
+}
+
+fn delete_objects_body(keys: &[String], quiet: bool) -> Result<String, Error> {
+    let delete = Delete {
+        quiet,
+        object: keys
+            .iter()
+            .map(|key| DeleteKey {
+                key: urlencoding::encode(key),
+            })
+            .collect(),
+    };
+    Ok(quick_xml::se::to_string_with_root("Delete", &delete)?)
+}
+
+/// Result of an AppendObject request.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AppendObjectResult {
+    /// Position of the next append, which is the length of the object.
+    pub next_position: u64,
+    /// CRC64 of the whole object after the append.
+    pub crc64: Option<u64>,
+}
+
+impl AppendObjectResult {
+    pub(crate) fn from_header_map(headers: &HeaderMap) -> Result<Self, Error> {
+        let number = |key: &str| {
+            headers
+                .get(key)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.parse().ok())
+        };
+        let next_position = number("x-oss-next-append-position").ok_or_else(|| {
+            Error::Object(ObjectError::PostError {
+                msg: "append response without x-oss-next-append-position".to_string(),
+            })
+        })?;
+        Ok(AppendObjectResult {
+            next_position,
+            crc64: number("x-oss-hash-crc64ecma"),
+        })
+    }
+}
+
+/// AppendObject request writing `buf` at `position`, headers such as
+/// `Content-Type` only apply to the first append.
+pub(crate) fn append_object_request<B, S1, S2, H>(
+    buf: B,
+    object_name: S1,
+    position: u64,
+    headers: H,
+) -> Request
+where
+    B: Into<Bytes>,
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+    H: Into<Option<HashMap<S2, S2>>>,
+{
+    Request::new(RequestType::Post, object_name, headers, None)
+        .resource("append", None::<String>)
+        .resource("position", Some(position.to_string()))
+        .body(buf)
+}
+
+/// A request of `object_name` with the headers and resources of `options`.
+pub(crate) fn object_request<S>(
+    req_type: RequestType,
+    object_name: S,
+    options: ObjectOptions,
+) -> Request
+where
+    S: AsRef<str>,
+{
+    let (headers, resources) = options.into_parts();
+    Request::new(req_type, object_name, Some(headers), Some(resources))
+}
+
+/// PutSymlink request of `link` pointing to `target`, the target being url
+/// encoded in `x-oss-symlink-target`.
+pub(crate) fn put_symlink_request<S1, S2>(
+    link: S1,
+    target: S2,
+    options: PutObjectOptions,
+) -> Request
+where
+    S1: AsRef<str>,
+    S2: AsRef<str>,
+{
+    let (headers, resources) = options.into_parts();
+    Request::new(RequestType::Put, link, Some(headers), Some(resources))
+        .resource("symlink", None::<String>)
+        .header("x-oss-symlink-target", urlencoding::encode(target.as_ref()))
+}
+
+/// The decoded `x-oss-symlink-target` of a GetSymlink response.
+pub(crate) fn symlink_target(headers: &HeaderMap) -> Result<String, Error> {
+    let target = headers
+        .get("x-oss-symlink-target")
+        .and_then(|v| v.to_str().ok())
+        .ok_or_else(|| {
+            Error::Object(ObjectError::GetError {
+                msg: "get symlink response without x-oss-symlink-target".to_string(),
+            })
+        })?;
+    Ok(urlencoding::decode(target)
+        .map(|t| t.into_owned())
+        .unwrap_or_else(|_| target.to_string()))
+}
+
+/// Reject the empty prefix, which would delete every object of the bucket.
+pub(crate) fn check_delete_prefix(prefix: &str) -> Result<(), Error> {
+    if prefix.is_empty() {
+        return Err(Error::Object(ObjectError::DeleteError {
+            msg: "refuse to delete every object of the bucket".to_string(),
+        }));
+    }
+    Ok(())
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "PascalCase")]
 pub struct Owner {
     #[serde(alias = "ID")]
@@ -117,6 +629,7 @@ pub struct Object {
     e_tag: String,
     r#type: String,
     storage_class: String,
+    #[serde(default)]
     owner: Owner,
 }
 
@@ -175,8 +688,30 @@ impl Object {
     }
 }
 
-trait PrivateObjectAPI {
-    fn generate_presigned_path<S1>(&self, object_name: S1, expires: usize) -> String
+/// Body of an object being downloaded, along with the response headers.
+#[derive(Debug)]
+pub struct ObjectReader {
+    resp: reqwest::blocking::Response,
+}
+
+impl ObjectReader {
+    pub fn headers(&self) -> &HeaderMap {
+        self.resp.headers()
+    }
+
+    pub fn meta(&self) -> Result<ObjectMeta, Error> {
+        ObjectMeta::from_header_map(self.resp.headers())
+    }
+}
+
+impl Read for ObjectReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.resp.read(buf)
+    }
+}
+
+pub(crate) trait PrivateObjectAPI {
+    fn generate_presigned_path<S1>(&self, object_name: S1, expires: usize) -> Result<String, Error>
     where
         S1: AsRef<str> + Send;
 }
@@ -188,6 +723,36 @@ pub trait ObjectAPI {
         H: Into<Option<HashMap<S, S>>>,
         R: Into<Option<HashMap<S, Option<S>>>>;
 
+    /// List objects with ListObjectsV2, `list-type=2` is added to the resources.
+    /// Pass `continuation-token`, `start-after`, `fetch-owner` or `encoding-type`
+    /// as resources.
+    fn list_objects_v2<S, H, R>(&self, headers: H, resources: R) -> Result<ListObjectsV2, Error>
+    where
+        S: AsRef<str>,
+        H: Into<Option<HashMap<S, S>>>,
+        R: Into<Option<HashMap<S, Option<S>>>>;
+
+    /// Iterate over every object and common prefix with ListObjectsV2,
+    /// following `next-continuation-token` until the listing is complete.
+    fn list_objects_v2_iter<S, H, R>(&self, headers: H, resources: R) -> ListObjectsV2Iter<'_, '_>
+    where
+        S: AsRef<str>,
+        H: Into<Option<HashMap<S, S>>>,
+        R: Into<Option<HashMap<S, Option<S>>>>;
+
+    /// List the versions and delete markers of the objects, `versions` is added
+    /// to the resources. Pass `prefix`, `delimiter`, `key-marker`,
+    /// `version-id-marker`, `max-keys` or `encoding-type` as resources.
+    fn list_object_versions<S, H, R>(
+        &self,
+        headers: H,
+        resources: R,
+    ) -> Result<ListObjectVersions, Error>
+    where
+        S: AsRef<str>,
+        H: Into<Option<HashMap<S, S>>>,
+        R: Into<Option<HashMap<S, Option<S>>>>;
+
     fn get_object<S1, S2, H, R>(
         &self,
         object_name: S1,
@@ -200,10 +765,48 @@ pub trait ObjectAPI {
         H: Into<Option<HashMap<S2, S2>>>,
         R: Into<Option<HashMap<S2, Option<S2>>>>;
 
+    /// Get an object without buffering it, the body is read from the returned
+    /// reader as it is received.
+    fn get_object_stream<S1, S2, H, R>(
+        &self,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<ObjectReader, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>;
+
+    /// Download an object into `file`, writing it as it is received.
+    fn get_object_to_file<S1, S2, S3, H, R>(
+        &self,
+        object_name: S1,
+        file: S2,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<Path>,
+        S3: AsRef<str>,
+        H: Into<Option<HashMap<S3, S3>>>,
+        R: Into<Option<HashMap<S3, Option<S3>>>>;
+
     fn get_object_acl<S>(&self, object_name: S) -> Result<String, Error>
     where
         S: AsRef<str>;
 
+    /// `get_object_acl` with typed options, e.g. of a previous version.
+    fn get_object_acl_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<String, Error>
+    where
+        S: AsRef<str>;
+
     fn put_object_from_file<S1, S2, S3, H, R>(
         &self,
         file: S1,
@@ -218,68 +821,405 @@ pub trait ObjectAPI {
         H: Into<Option<HashMap<S3, S3>>>,
         R: Into<Option<HashMap<S3, Option<S3>>>>;
 
-    fn put_object_from_buffer<S1, S2, H, R>(
+    fn put_object_from_buffer<B, S1, S2, H, R>(
         &self,
-        buf: &[u8],
+        buf: B,
         object_name: S1,
         headers: H,
         resources: R,
     ) -> Result<(), Error>
     where
+        B: Into<Bytes>,
         S1: AsRef<str>,
         S2: AsRef<str>,
         H: Into<Option<HashMap<S2, S2>>>,
         R: Into<Option<HashMap<S2, Option<S2>>>>;
 
-    fn get_object_signed_url<S1>(&self, object_name: S1, expires: usize) -> String
-    where
-        S1: AsRef<str> + Send;
-
-    fn copy_object_from_object<S1, S2, S3, H, R>(
+    /// Upload the `len` bytes read from `reader`, without buffering them. The
+    /// body can not be replayed, so the request is never retried.
+    fn put_object_from_reader<S1, S2, H, R, Rd>(
         &self,
-        src: S1,
-        dest: S2,
+        reader: Rd,
+        len: u64,
+        object_name: S1,
         headers: H,
         resources: R,
     ) -> Result<(), Error>
     where
         S1: AsRef<str>,
         S2: AsRef<str>,
-        S3: AsRef<str>,
-        H: Into<Option<HashMap<S3, S3>>>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>,
+        Rd: Read + Send + 'static;
+
+    /// Presigned url to GET the object until the unix time `expires`. Panics
+    /// when the credentials provider fails.
+    #[deprecated(since = "0.10.0", note = "use `try_get_object_signed_url`")]
+    fn get_object_signed_url<S1>(&self, object_name: S1, expires: usize) -> String
+    where
+        S1: AsRef<str> + Send;
+
+    /// Presigned url to GET the object until the unix time `expires`. The url
+    /// is built locally, no request is sent.
+    fn try_get_object_signed_url<S1>(
+        &self,
+        object_name: S1,
+        expires: usize,
+    ) -> Result<String, Error>
+    where
+        S1: AsRef<str> + Send;
+
+    fn copy_object_from_object<S1, S2, S3, H, R>(
+        &self,
+        src: S1,
+        dest: S2,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        S3: AsRef<str>,
+        H: Into<Option<HashMap<S3, S3>>>,
         R: Into<Option<HashMap<S3, Option<S3>>>>;
 
+    fn head_object<S>(&self, object_name: S) -> Result<ObjectMeta, Error>
+    where
+        S: AsRef<str>;
+
+    /// `head_object` with typed options, e.g. of a previous version.
+    fn head_object_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<ObjectMeta, Error>
+    where
+        S: AsRef<str>;
+
+    /// Append `buf` to an appendable object at `position`, which must be its
+    /// current length, 0 creating the object.
+    fn append_object<B, S1, S2, H>(
+        &self,
+        buf: B,
+        object_name: S1,
+        position: u64,
+        headers: H,
+    ) -> Result<AppendObjectResult, Error>
+    where
+        B: Into<Bytes>,
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>;
+
+    /// Create `link` as a symlink to the object `target`, `options` setting
+    /// the storage class, ACL or metadata of the link. Returns the version id
+    /// of the link on versioned buckets.
+    fn put_symlink<S1, S2>(
+        &self,
+        link: S1,
+        target: S2,
+        options: PutObjectOptions,
+    ) -> Result<Option<String>, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>;
+
+    /// The object the symlink `link` points to.
+    fn get_symlink<S>(&self, link: S) -> Result<String, Error>
+    where
+        S: AsRef<str>;
+
+    /// Restore an archived object, see [`restore`](crate::restore) to wait
+    /// until it is readable.
+    fn restore_object<S>(&self, object_name: S, request: &RestoreRequest) -> Result<(), Error>
+    where
+        S: AsRef<str>;
+
+    /// `copy_object_from_object` with typed options, returning the version id
+    /// of `dest` on versioned buckets.
+    fn copy_object_with_options<S1, S2>(
+        &self,
+        src: S1,
+        dest: S2,
+        options: CopyObjectOptions,
+    ) -> Result<Option<String>, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>;
+
+    /// Replace the tags of an object.
+    fn put_object_tagging<S>(&self, object_name: S, tagging: &Tagging) -> Result<(), Error>
+    where
+        S: AsRef<str>;
+
+    fn get_object_tagging<S>(&self, object_name: S) -> Result<Tagging, Error>
+    where
+        S: AsRef<str>;
+
+    fn delete_object_tagging<S>(&self, object_name: S) -> Result<(), Error>
+    where
+        S: AsRef<str>;
+
+    /// `put_object_tagging` with typed options, e.g. to tag a previous version.
+    fn put_object_tagging_with_options<S>(
+        &self,
+        object_name: S,
+        tagging: &Tagging,
+        options: ObjectOptions,
+    ) -> Result<(), Error>
+    where
+        S: AsRef<str>;
+
+    /// `get_object_tagging` with typed options, e.g. of a previous version.
+    fn get_object_tagging_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<Tagging, Error>
+    where
+        S: AsRef<str>;
+
+    /// `delete_object_tagging` with typed options, e.g. of a previous version.
+    fn delete_object_tagging_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<(), Error>
+    where
+        S: AsRef<str>;
+
+    /// Notify oss to init a Multipart Upload event
+    fn init_multi<S1, S2, H, R>(
+        &self,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<InitiateMultipartUploadResult, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>;
+
+    /// Upload data in chunks according to the specified Object name and uploadId
+    fn upload_part<B, S1, S2, H, R>(
+        &self,
+        buf: B,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<String, Error>
+    where
+        B: Into<Bytes>,
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>;
+
+    /// `upload_part` of the `len` bytes read from `reader`, without loading
+    /// the part in memory. The body can not be replayed, so the request is
+    /// never retried.
+    fn upload_part_from_reader<S1, S2, H, R, Rd>(
+        &self,
+        reader: Rd,
+        len: u64,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<String, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>,
+        Rd: Read + Send + 'static;
+
+    /// List the parts uploaded so far, up to `max-parts` from `part-number-marker`
+    fn list_parts<S1, S2, H, R>(
+        &self,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<ListPartsResult, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>;
+
+    /// Complete the multipart upload of the entire file, `body` being built
+    /// with [`CompleteMultipartUpload`](crate::multi_part::CompleteMultipartUpload).
+    fn complete_multi<S1, S2, H, R>(
+        &self,
+        body: String,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<CompleteMultipartUploadResult, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>;
+
+    /// Cancel the MultipartUpload event and delete the corresponding Part data
+    fn abort_multi<S1, S2, H, R>(
+        &self,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>;
+
+    /// `get_object` with typed options.
+    fn get_object_with_options<S>(
+        &self,
+        object_name: S,
+        options: GetObjectOptions,
+    ) -> Result<Vec<u8>, Error>
+    where
+        S: AsRef<str>;
+
+    /// `put_object_from_buffer` with typed options, returning the version id
+    /// of the object on versioned buckets.
+    fn put_object_with_options<B, S>(
+        &self,
+        buf: B,
+        object_name: S,
+        options: PutObjectOptions,
+    ) -> Result<Option<String>, Error>
+    where
+        B: Into<Bytes>,
+        S: AsRef<str>;
+
     fn delete_object<S>(&self, object_name: S) -> Result<(), Error>
     where
         S: AsRef<str>;
+
+    /// `delete_object` with typed options. With a version id the version is
+    /// permanently deleted, or the delete marker removed to restore the object.
+    fn delete_object_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<DeleteObjectResult, Error>
+    where
+        S: AsRef<str>;
+
+    /// Delete several objects with DeleteMultipleObjects, in batches of
+    /// [`DELETE_OBJECTS_BATCH`] keys. The batches before a failed one are
+    /// already deleted when an error is returned.
+    fn delete_objects<S, I>(&self, keys: I, quiet: bool) -> Result<DeleteObjectsResult, Error>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = S>;
+
+    /// Delete every object whose key starts with `prefix`, which can not be
+    /// empty, with quiet requests so that only the failed keys are kept.
+    fn delete_prefix<S>(&self, prefix: S) -> Result<DeletePrefixResult, Error>
+    where
+        S: AsRef<str>;
 }
 
 impl<'a> PrivateObjectAPI for OSS<'a> {
-    fn generate_presigned_path<S1>(&self, object_name: S1, expires: usize) -> String
+    fn generate_presigned_path<S1>(&self, object_name: S1, expires: usize) -> Result<String, Error>
     where
         S1: AsRef<str> + Send,
     {
         let object_name = object_name.as_ref();
-        let mut headers = HeaderMap::new();
-        headers.insert(DATE, HeaderValue::from_str(&expires.to_string()).unwrap());
-        let signature = self.sign(
-            RequestType::Get.as_str(),
-            self.key_secret(),
-            self.bucket(),
-            object_name,
-            "",
-            &headers,
-        );
-        format!(
-            "/{}?Expires={}&OSSAccessKeyId={}&Signature={}",
-            urlencoding::encode(object_name),
-            expires,
-            urlencoding::encode(self.key_id()),
-            urlencoding::encode(&signature)
-        )
+        let credentials = self.credentials()?;
+        let path = match self.signature_version() {
+            SignatureVersion::V1 => {
+                let mut headers = HeaderMap::new();
+                headers.insert(DATE, HeaderValue::from_str(&expires.to_string()).unwrap());
+                let resources_str = credentials
+                    .security_token()
+                    .map(|token| format!("security-token={}", token))
+                    .unwrap_or_default();
+                let signature = self.sign(
+                    RequestType::Get.as_str(),
+                    credentials.access_key_secret(),
+                    self.bucket(),
+                    object_name,
+                    &resources_str,
+                    &headers,
+                );
+                let mut path = format!(
+                    "/{}?Expires={}&OSSAccessKeyId={}&Signature={}",
+                    urlencoding::encode(object_name),
+                    expires,
+                    urlencoding::encode(credentials.access_key_id()),
+                    urlencoding::encode(&signature)
+                );
+                if let Some(token) = credentials.security_token() {
+                    path += &format!("&security-token={}", urlencoding::encode(token));
+                }
+                path
+            }
+            SignatureVersion::V4 => {
+                // V4 takes a lifetime in seconds rather than an absolute expiry
+                let now = Utc::now().timestamp().max(0) as usize;
+                presigned_path_v4(
+                    self,
+                    object_name,
+                    &credentials,
+                    &self.date_time(),
+                    expires.saturating_sub(now),
+                )
+            }
+        };
+        Ok(path)
     }
 }
 
+/// Path and query of a V4 presigned GET of `object_name`, valid for
+/// `expires_in` seconds from `date_time`.
+fn presigned_path_v4(
+    oss: &OSS,
+    object_name: &str,
+    credentials: &Credentials,
+    date_time: &str,
+    expires_in: usize,
+) -> String {
+    let expires = expires_in.to_string();
+    let credential = get_v4_credential(credentials.access_key_id(), &date_time[..8], oss.region());
+    let mut params = vec![
+        ("x-oss-signature-version", Some(OSS4_ALGORITHM)),
+        ("x-oss-credential", Some(credential.as_str())),
+        ("x-oss-date", Some(date_time)),
+        ("x-oss-expires", Some(expires.as_str())),
+    ];
+    if let Some(token) = credentials.security_token() {
+        params.push(("x-oss-security-token", Some(token)));
+    }
+    let signature = oss.sign_v4(
+        RequestType::Get.as_str(),
+        credentials.access_key_secret(),
+        oss.region(),
+        oss.bucket(),
+        object_name,
+        &params,
+        &HeaderMap::new(),
+        date_time,
+    );
+    let mut path = format!(
+        "/{}?x-oss-signature-version={}&x-oss-credential={}&x-oss-date={}&x-oss-expires={}",
+        urlencoding::encode(object_name),
+        OSS4_ALGORITHM,
+        urlencoding::encode(&credential),
+        date_time,
+        expires,
+    );
+    if let Some(token) = credentials.security_token() {
+        path += &format!("&x-oss-security-token={}", urlencoding::encode(token));
+    }
+    path + &format!("&x-oss-signature={}", signature)
+}
+
 impl<'a> ObjectAPI for OSS<'a> {
     fn list_object<S, H, R>(&self, headers: H, resources: R) -> Result<ListObjects, Error>
     where
@@ -287,13 +1227,12 @@ impl<'a> ObjectAPI for OSS<'a> {
         H: Into<Option<HashMap<S, S>>>,
         R: Into<Option<HashMap<S, Option<S>>>>,
     {
-        let (host, headers) =
-            self.build_request(RequestType::Get, String::new(), headers, resources)?;
-
-        let resp = reqwest::blocking::Client::new()
-            .get(&host)
-            .headers(headers)
-            .send()?;
+        let resp = self.send_blocking(Request::new(
+            RequestType::Get,
+            String::new(),
+            headers,
+            resources,
+        ))?;
 
         let body = resp.text()?;
         let list_objects = quick_xml::de::from_str::<ListObjects>(&body)?;
@@ -301,6 +1240,56 @@ impl<'a> ObjectAPI for OSS<'a> {
         Ok(list_objects)
     }
 
+    fn list_objects_v2<S, H, R>(&self, headers: H, resources: R) -> Result<ListObjectsV2, Error>
+    where
+        S: AsRef<str>,
+        H: Into<Option<HashMap<S, S>>>,
+        R: Into<Option<HashMap<S, Option<S>>>>,
+    {
+        let resp = self.send_blocking(
+            Request::new(RequestType::Get, String::new(), headers, resources)
+                .resource("list-type", Some("2")),
+        )?;
+
+        let body = resp.text()?;
+        ListObjectsV2::from_xml(&body)
+    }
+
+    fn list_objects_v2_iter<S, H, R>(&self, headers: H, resources: R) -> ListObjectsV2Iter<'_, '_>
+    where
+        S: AsRef<str>,
+        H: Into<Option<HashMap<S, S>>>,
+        R: Into<Option<HashMap<S, Option<S>>>>,
+    {
+        let request = Request::new(RequestType::Get, String::new(), headers, resources);
+        ListObjectsV2Iter {
+            oss: self,
+            headers: request.headers().clone(),
+            resources: request.resources().clone(),
+            token: None,
+            entries: Vec::new().into_iter(),
+            done: false,
+        }
+    }
+
+    fn list_object_versions<S, H, R>(
+        &self,
+        headers: H,
+        resources: R,
+    ) -> Result<ListObjectVersions, Error>
+    where
+        S: AsRef<str>,
+        H: Into<Option<HashMap<S, S>>>,
+        R: Into<Option<HashMap<S, Option<S>>>>,
+    {
+        let resp = self.send_blocking(
+            Request::new(RequestType::Get, String::new(), headers, resources)
+                .resource("versions", None::<String>),
+        )?;
+
+        ListObjectVersions::from_xml(&resp.text()?)
+    }
+
     fn get_object<S1, S2, H, R>(
         &self,
         object_name: S1,
@@ -313,61 +1302,105 @@ impl<'a> ObjectAPI for OSS<'a> {
         H: Into<Option<HashMap<S2, S2>>>,
         R: Into<Option<HashMap<S2, Option<S2>>>>,
     {
-        let (host, headers) =
-            self.build_request(RequestType::Get, object_name, headers, resources)?;
-
-        let mut resp = reqwest::blocking::Client::new()
-            .get(&host)
-            .headers(headers)
-            .send()?;
+        let mut resp = self.send_blocking(Request::new(
+            RequestType::Get,
+            object_name,
+            headers,
+            resources,
+        ))?;
         let mut buf: Vec<u8> = vec![];
+        resp.copy_to(&mut buf)?;
+        Ok(buf)
+    }
 
-        if resp.status().is_success() {
-            resp.copy_to(&mut buf)?;
-            Ok(buf)
-        } else {
-            Err(Error::Object(ObjectError::GetError {
-                msg: format!("can not get object, status code: {}", resp.status()).into(),
-            }))
-        }
+    fn get_object_stream<S1, S2, H, R>(
+        &self,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<ObjectReader, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>,
+    {
+        let resp = self.send_blocking(Request::new(
+            RequestType::Get,
+            object_name,
+            headers,
+            resources,
+        ))?;
+
+        Ok(ObjectReader { resp })
+    }
+
+    fn get_object_to_file<S1, S2, S3, H, R>(
+        &self,
+        object_name: S1,
+        file: S2,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<Path>,
+        S3: AsRef<str>,
+        H: Into<Option<HashMap<S3, S3>>>,
+        R: Into<Option<HashMap<S3, Option<S3>>>>,
+    {
+        let mut reader = self.get_object_stream(object_name, headers, resources)?;
+
+        let mut f = BufWriter::new(File::create(file)?);
+        io::copy(&mut reader, &mut f)?;
+        f.flush()?;
+        Ok(())
     }
 
     fn get_object_acl<S>(&self, object_name: S) -> Result<String, Error>
     where
         S: AsRef<str>,
     {
-        let object_name = object_name.as_ref();
-        let mut params: HashMap<&str, Option<&str>> = HashMap::new();
-        params.insert("acl", None);
-        let result = String::from_utf8(self.get_object(object_name, None, Some(params))?)?;
-        let mut reader = Reader::from_str(&result);
-        reader.trim_text(true);
-        let mut grant = String::new();
+        self.get_object_acl_with_options(object_name, ObjectOptions::new())
+    }
 
-        loop {
-            match reader.read_event() {
-                Ok(Event::Start(ref e)) if e.name().as_ref() == b"Grant" => {
-                    grant = reader.read_text(e.name())?.to_string();
-                }
-                Ok(Event::Eof) => break,
-                Err(e) => panic!("Error at position {}: {:?}", reader.buffer_position(), e),
-                _ => (),
-            }
-        }
+    fn get_object_acl_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<String, Error>
+    where
+        S: AsRef<str>,
+    {
+        let resp = self.send_blocking(
+            object_request(RequestType::Get, object_name, options).resource("acl", None::<String>),
+        )?;
 
-        Ok(grant)
+        parse_object_acl(&resp.text()?)
     }
 
     fn get_object_signed_url<S1>(&self, object_name: S1, expires: usize) -> String
     where
         S1: AsRef<str> + Send,
     {
-        format!(
+        self.try_get_object_signed_url(object_name, expires)
+            .expect("credentials to sign the url")
+    }
+
+    fn try_get_object_signed_url<S1>(
+        &self,
+        object_name: S1,
+        expires: usize,
+    ) -> Result<String, Error>
+    where
+        S1: AsRef<str> + Send,
+    {
+        Ok(format!(
             "https://{}.{}{}",
             self.bucket(),
             self.endpoint(),
-            self.generate_presigned_path(object_name, expires),
-        )
+            self.generate_presigned_path(object_name, expires)?,
+        ))
     }
 
     fn put_object_from_file<S1, S2, S3, H, R>(
@@ -384,29 +1417,37 @@ impl<'a> ObjectAPI for OSS<'a> {
         H: Into<Option<HashMap<S3, S3>>>,
         R: Into<Option<HashMap<S3, Option<S3>>>>,
     {
-        let (host, headers) =
-            self.build_request(RequestType::Put, object_name, headers, resources)?;
-
-        let buf = load_file(file)?;
+        let request = Request::new(RequestType::Put, object_name, headers, resources);
+        let len = std::fs::metadata(file.as_ref())?.len();
 
-        let resp = reqwest::blocking::Client::new()
-            .put(&host)
-            .headers(headers)
-            .body(buf)
-            .send()?;
+        self.send_blocking(request.file_body(file.as_ref(), len))?;
+        Ok(())
+    }
 
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(Error::Object(ObjectError::PutError {
-                msg: format!("can not put object, status code: {}", resp.status()).into(),
-            }))
-        }
+    fn put_object_from_buffer<B, S1, S2, H, R>(
+        &self,
+        buf: B,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        B: Into<Bytes>,
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>,
+    {
+        self.send_blocking(
+            Request::new(RequestType::Put, object_name, headers, resources).body(buf),
+        )?;
+        Ok(())
     }
 
-    fn put_object_from_buffer<S1, S2, H, R>(
+    fn put_object_from_reader<S1, S2, H, R, Rd>(
         &self,
-        buf: &[u8],
+        reader: Rd,
+        len: u64,
         object_name: S1,
         headers: H,
         resources: R,
@@ -416,23 +1457,13 @@ impl<'a> ObjectAPI for OSS<'a> {
         S2: AsRef<str>,
         H: Into<Option<HashMap<S2, S2>>>,
         R: Into<Option<HashMap<S2, Option<S2>>>>,
+        Rd: Read + Send + 'static,
     {
-        let (host, headers) =
-            self.build_request(RequestType::Put, object_name, headers, resources)?;
-
-        let resp = reqwest::blocking::Client::new()
-            .put(&host)
-            .headers(headers)
-            .body(buf.to_owned())
-            .send()?;
-
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(Error::Object(ObjectError::PutError {
-                msg: format!("can not put object, status code: {}", resp.status()).into(),
-            }))
-        }
+        self.send_blocking(
+            Request::new(RequestType::Put, object_name, headers, resources)
+                .reader_body(reqwest::blocking::Body::sized(reader, len), len),
+        )?;
+        Ok(())
     }
 
     fn copy_object_from_object<S1, S2, S3, H, R>(
@@ -449,43 +1480,600 @@ impl<'a> ObjectAPI for OSS<'a> {
         H: Into<Option<HashMap<S3, S3>>>,
         R: Into<Option<HashMap<S3, Option<S3>>>>,
     {
-        let (host, mut headers) =
-            self.build_request(RequestType::Put, object_name, headers, resources)?;
-        headers.insert("x-oss-copy-source", src.as_ref().parse()?);
-
-        let resp = reqwest::blocking::Client::new()
-            .put(&host)
-            .headers(headers)
-            .send()?;
-
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(Error::Object(ObjectError::CopyError {
-                msg: format!("can not copy object, status code: {}", resp.status()).into(),
-            }))
-        }
+        self.send_blocking(
+            Request::new(RequestType::Put, object_name, headers, resources)
+                .header("x-oss-copy-source", src.as_ref()),
+        )?;
+        Ok(())
+    }
+
+    fn head_object<S>(&self, object_name: S) -> Result<ObjectMeta, Error>
+    where
+        S: AsRef<str>,
+    {
+        self.head_object_with_options(object_name, ObjectOptions::new())
+    }
+
+    fn head_object_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<ObjectMeta, Error>
+    where
+        S: AsRef<str>,
+    {
+        let resp = self.send_blocking(object_request(RequestType::Head, object_name, options))?;
+
+        ObjectMeta::from_header_map(resp.headers())
+    }
+
+    fn append_object<B, S1, S2, H>(
+        &self,
+        buf: B,
+        object_name: S1,
+        position: u64,
+        headers: H,
+    ) -> Result<AppendObjectResult, Error>
+    where
+        B: Into<Bytes>,
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+    {
+        let resp =
+            self.send_blocking(append_object_request(buf, object_name, position, headers))?;
+
+        AppendObjectResult::from_header_map(resp.headers())
+    }
+
+    fn put_symlink<S1, S2>(
+        &self,
+        link: S1,
+        target: S2,
+        options: PutObjectOptions,
+    ) -> Result<Option<String>, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+    {
+        let resp = self.send_blocking(put_symlink_request(link, target, options))?;
+
+        Ok(version_id(resp.headers()))
+    }
+
+    fn get_symlink<S>(&self, link: S) -> Result<String, Error>
+    where
+        S: AsRef<str>,
+    {
+        let resp = self.send_blocking(
+            Request::new(
+                RequestType::Get,
+                link,
+                None::<HashMap<String, String>>,
+                None,
+            )
+            .resource("symlink", None::<String>),
+        )?;
+
+        symlink_target(resp.headers())
+    }
+
+    fn restore_object<S>(&self, object_name: S, request: &RestoreRequest) -> Result<(), Error>
+    where
+        S: AsRef<str>,
+    {
+        self.send_blocking(restore_object_request(object_name, request)?)?;
+        Ok(())
+    }
+
+    fn copy_object_with_options<S1, S2>(
+        &self,
+        src: S1,
+        dest: S2,
+        options: CopyObjectOptions,
+    ) -> Result<Option<String>, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+    {
+        let src = options.copy_source(src.as_ref());
+        let (headers, resources) = options.into_parts();
+        let resp = self.send_blocking(
+            Request::new(RequestType::Put, dest, Some(headers), Some(resources))
+                .header("x-oss-copy-source", src),
+        )?;
+
+        Ok(version_id(resp.headers()))
+    }
+
+    fn put_object_tagging<S>(&self, object_name: S, tagging: &Tagging) -> Result<(), Error>
+    where
+        S: AsRef<str>,
+    {
+        self.put_object_tagging_with_options(object_name, tagging, ObjectOptions::new())
+    }
+
+    fn get_object_tagging<S>(&self, object_name: S) -> Result<Tagging, Error>
+    where
+        S: AsRef<str>,
+    {
+        self.get_object_tagging_with_options(object_name, ObjectOptions::new())
+    }
+
+    fn delete_object_tagging<S>(&self, object_name: S) -> Result<(), Error>
+    where
+        S: AsRef<str>,
+    {
+        self.delete_object_tagging_with_options(object_name, ObjectOptions::new())
+    }
+
+    fn put_object_tagging_with_options<S>(
+        &self,
+        object_name: S,
+        tagging: &Tagging,
+        options: ObjectOptions,
+    ) -> Result<(), Error>
+    where
+        S: AsRef<str>,
+    {
+        self.send_blocking(
+            tagging_request(RequestType::Put, object_name, options).body(tagging.to_xml()?),
+        )?;
+        Ok(())
+    }
+
+    fn get_object_tagging_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<Tagging, Error>
+    where
+        S: AsRef<str>,
+    {
+        let resp = self.send_blocking(tagging_request(RequestType::Get, object_name, options))?;
+
+        Tagging::from_xml(&resp.text()?)
+    }
+
+    fn delete_object_tagging_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<(), Error>
+    where
+        S: AsRef<str>,
+    {
+        self.send_blocking(tagging_request(RequestType::Delete, object_name, options))?;
+        Ok(())
+    }
+
+    fn init_multi<S1, S2, H, R>(
+        &self,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<InitiateMultipartUploadResult, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>,
+    {
+        let resp = self.send_blocking(Request::new(
+            RequestType::Post,
+            object_name,
+            headers,
+            resources,
+        ))?;
+
+        let body = resp.text()?;
+        let res = quick_xml::de::from_str::<InitiateMultipartUploadResult>(&body)?;
+        Ok(res)
+    }
+
+    fn upload_part<B, S1, S2, H, R>(
+        &self,
+        buf: B,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<String, Error>
+    where
+        B: Into<Bytes>,
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>,
+    {
+        let resp = self.send_blocking(
+            Request::new(RequestType::Put, object_name, headers, resources).body(buf),
+        )?;
+
+        upload_part_e_tag(resp.headers())
+    }
+
+    fn upload_part_from_reader<S1, S2, H, R, Rd>(
+        &self,
+        reader: Rd,
+        len: u64,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<String, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>,
+        Rd: Read + Send + 'static,
+    {
+        let resp = self.send_blocking(
+            Request::new(RequestType::Put, object_name, headers, resources)
+                .reader_body(reqwest::blocking::Body::sized(reader, len), len),
+        )?;
+
+        upload_part_e_tag(resp.headers())
+    }
+
+    fn list_parts<S1, S2, H, R>(
+        &self,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<ListPartsResult, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>,
+    {
+        let resp = self.send_blocking(Request::new(
+            RequestType::Get,
+            object_name,
+            headers,
+            resources,
+        ))?;
+
+        let body = resp.text()?;
+        let res = quick_xml::de::from_str::<ListPartsResult>(&body)?;
+        Ok(res)
+    }
+
+    fn complete_multi<S1, S2, H, R>(
+        &self,
+        body: String,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<CompleteMultipartUploadResult, Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>,
+    {
+        let resp = self.send_blocking(
+            Request::new(RequestType::Post, object_name, headers, resources).body(body),
+        )?;
+
+        let body = resp.text()?;
+        let res = quick_xml::de::from_str::<CompleteMultipartUploadResult>(&body)?;
+        Ok(res)
+    }
+
+    fn abort_multi<S1, S2, H, R>(
+        &self,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Result<(), Error>
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>,
+    {
+        self.send_blocking(Request::new(
+            RequestType::Delete,
+            object_name,
+            headers,
+            resources,
+        ))?;
+        Ok(())
+    }
+
+    fn get_object_with_options<S>(
+        &self,
+        object_name: S,
+        options: GetObjectOptions,
+    ) -> Result<Vec<u8>, Error>
+    where
+        S: AsRef<str>,
+    {
+        let (headers, resources) = options.into_parts();
+        self.get_object(object_name, headers, resources)
+    }
+
+    fn put_object_with_options<B, S>(
+        &self,
+        buf: B,
+        object_name: S,
+        options: PutObjectOptions,
+    ) -> Result<Option<String>, Error>
+    where
+        B: Into<Bytes>,
+        S: AsRef<str>,
+    {
+        let (headers, resources) = options.into_parts();
+        let resp = self.send_blocking(
+            Request::new(
+                RequestType::Put,
+                object_name,
+                Some(headers),
+                Some(resources),
+            )
+            .body(buf),
+        )?;
+
+        Ok(version_id(resp.headers()))
     }
 
     fn delete_object<S>(&self, object_name: S) -> Result<(), Error>
     where
         S: AsRef<str>,
     {
-        let headers = HashMap::<String, String>::new();
-        let (host, headers) =
-            self.build_request(RequestType::Delete, object_name, Some(headers), None)?;
-
-        let resp = reqwest::blocking::Client::new()
-            .delete(&host)
-            .headers(headers)
-            .send()?;
-
-        if resp.status().is_success() {
-            Ok(())
-        } else {
-            Err(Error::Object(ObjectError::DeleteError {
-                msg: format!("can not delete object, status code: {}", resp.status()).into(),
-            }))
+        self.delete_object_with_options(object_name, ObjectOptions::new())?;
+        Ok(())
+    }
+
+    fn delete_object_with_options<S>(
+        &self,
+        object_name: S,
+        options: ObjectOptions,
+    ) -> Result<DeleteObjectResult, Error>
+    where
+        S: AsRef<str>,
+    {
+        let resp = self.send_blocking(object_request(RequestType::Delete, object_name, options))?;
+
+        Ok(DeleteObjectResult::from_header_map(resp.headers()))
+    }
+
+    fn delete_objects<S, I>(&self, keys: I, quiet: bool) -> Result<DeleteObjectsResult, Error>
+    where
+        S: AsRef<str>,
+        I: IntoIterator<Item = S>,
+    {
+        let keys: Vec<String> = keys.into_iter().map(|k| k.as_ref().to_string()).collect();
+        let mut result = DeleteObjectsResult::default();
+        for batch in keys.chunks(DELETE_OBJECTS_BATCH) {
+            let resp = self.send_blocking(delete_objects_request(batch, quiet)?)?;
+            result.extend(DeleteObjectsResult::from_xml(&resp.text()?)?);
+        }
+        Ok(result)
+    }
+
+    fn delete_prefix<S>(&self, prefix: S) -> Result<DeletePrefixResult, Error>
+    where
+        S: AsRef<str>,
+    {
+        check_delete_prefix(prefix.as_ref())?;
+        let mut resources = HashMap::new();
+        resources.insert("prefix", Some(prefix.as_ref()));
+
+        let mut result = DeletePrefixResult::default();
+        let mut keys = Vec::with_capacity(DELETE_OBJECTS_BATCH);
+        for entry in self.list_objects_v2_iter(None, Some(resources)) {
+            if let ListEntry::Object(object) = entry? {
+                keys.push(object.key);
+            }
+            if keys.len() == DELETE_OBJECTS_BATCH {
+                result.add_batch(keys.len(), self.delete_objects(keys.drain(..), true)?);
+            }
+        }
+        if !keys.is_empty() {
+            result.add_batch(keys.len(), self.delete_objects(keys, true)?);
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::async_object::AsyncObjectAPI;
+    use crate::oss::Options;
+    use crate::test_server::{self, Received, Response};
+    use futures_util::TryStreamExt;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn v4_presigned_path() {
+        let oss = OSS::new_with_opts(
+            "ak",
+            "sk",
+            "oss-cn-hangzhou.aliyuncs.com",
+            "bucket",
+            Options {
+                signature_version: SignatureVersion::V4,
+                ..Default::default()
+            },
+        );
+        let credentials = Credentials::new("ak", "sk");
+
+        assert_eq!(
+            presigned_path_v4(
+                &oss,
+                "1234+-/123/1.txt",
+                &credentials,
+                "20231217T025437Z",
+                599
+            ),
+            "/1234%2B-%2F123%2F1.txt\
+             ?x-oss-signature-version=OSS4-HMAC-SHA256\
+             &x-oss-credential=ak%2F20231217%2Fcn-hangzhou%2Foss%2Faliyun_v4_request\
+             &x-oss-date=20231217T025437Z\
+             &x-oss-expires=599\
+             &x-oss-signature=6ebba124f9d62b0a8718efbce2f6942e3f4d6e376773558a8e22ef88d9685a4a"
+        );
+
+        let credentials = credentials.with_security_token("token/1");
+        let path = presigned_path_v4(&oss, "a.txt", &credentials, "20231217T025437Z", 60);
+        assert!(path.contains("&x-oss-security-token=token%2F1&x-oss-signature="));
+    }
+
+    #[test]
+    fn delete_objects_body_encodes_the_keys() {
+        let keys = vec!["a b/c&d.txt".to_string(), "e\u{1}f".to_string()];
+        assert_eq!(
+            delete_objects_body(&keys, true).unwrap(),
+            "<Delete><Quiet>true</Quiet>\
+             <Object><Key>a%20b%2Fc%26d.txt</Key></Object>\
+             <Object><Key>e%01f</Key></Object></Delete>"
+        );
+    }
+
+    #[test]
+    fn delete_objects_result_decodes_the_keys() {
+        let result = DeleteObjectsResult::from_xml(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+            <DeleteResult>
+              <EncodingType>url</EncodingType>
+              <Error>
+                <Key>a%20b%2Fc%26d.txt</Key>
+                <Code>AccessDenied</Code>
+                <Message>Access Denied</Message>
+              </Error>
+            </DeleteResult>"#,
+        )
+        .unwrap();
+        assert!(result.deleted().is_empty());
+        assert_eq!(result.failed()[0].key(), "a b/c&d.txt");
+        assert_eq!(result.failed()[0].code(), "AccessDenied");
+
+        let mut prefix = DeletePrefixResult::default();
+        prefix.add_batch(3, result);
+        prefix.add_batch(2, DeleteObjectsResult::from_xml("").unwrap());
+        assert_eq!(prefix.deleted(), 4);
+        assert_eq!(prefix.failed().len(), 1);
+    }
+
+    /// Serve a listing of two pages, the second one url encoded, recording the
+    /// requests.
+    fn list_server() -> (String, Arc<Mutex<Vec<Received>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let url = test_server::serve(move |req| {
+            let page = match req.query("continuation-token") {
+                None => {
+                    "<ListBucketResult><Name>bucket</Name><Prefix>photos/</Prefix>\
+                     <MaxKeys>2</MaxKeys><Delimiter>/</Delimiter>\
+                     <IsTruncated>true</IsTruncated>\
+                     <NextContinuationToken>page-2</NextContinuationToken>\
+                     <KeyCount>2</KeyCount>\
+                     <Contents><Key>photos/a.jpg</Key>\
+                     <LastModified>2023-12-17T02:54:37.000Z</LastModified>\
+                     <ETag>\"A\"</ETag><Type>Normal</Type><Size>1</Size>\
+                     <StorageClass>Standard</StorageClass></Contents>\
+                     <CommonPrefixes><Prefix>photos/2023/</Prefix></CommonPrefixes>\
+                     </ListBucketResult>"
+                }
+                Some(_) => {
+                    "<ListBucketResult><Name>bucket</Name><Prefix>photos%2F</Prefix>\
+                     <MaxKeys>2</MaxKeys><Delimiter>%2F</Delimiter>\
+                     <EncodingType>url</EncodingType>\
+                     <IsTruncated>false</IsTruncated>\
+                     <ContinuationToken>page-2</ContinuationToken>\
+                     <KeyCount>2</KeyCount>\
+                     <Contents><Key>photos%2Fb%20%26%20c.jpg</Key>\
+                     <LastModified>2023-12-17T02:54:37.000Z</LastModified>\
+                     <ETag>\"B\"</ETag><Type>Normal</Type><Size>2</Size>\
+                     <StorageClass>Standard</StorageClass></Contents>\
+                     <CommonPrefixes><Prefix>photos%2F2024%20%231%2F</Prefix></CommonPrefixes>\
+                     </ListBucketResult>"
+                }
+            };
+            log.lock().unwrap().push(req);
+            Response::ok(page)
+        });
+        (url, requests)
+    }
+
+    fn list_resources() -> HashMap<&'static str, Option<&'static str>> {
+        let mut resources = HashMap::new();
+        resources.insert("prefix", Some("photos/"));
+        resources.insert("delimiter", Some("/"));
+        resources.insert("encoding-type", Some("url"));
+        resources
+    }
+
+    fn entry_names(entries: &[ListEntry]) -> Vec<String> {
+        entries
+            .iter()
+            .map(|entry| match entry {
+                ListEntry::Object(object) => object.key().to_string(),
+                ListEntry::CommonPrefix(prefix) => format!("prefix {}", prefix.prefix()),
+            })
+            .collect()
+    }
+
+    fn assert_two_pages_listed(entries: &[ListEntry], requests: &[Received]) {
+        assert_eq!(
+            entry_names(entries),
+            [
+                "photos/a.jpg",
+                "prefix photos/2023/",
+                "photos/b & c.jpg",
+                "prefix photos/2024 #1/",
+            ]
+        );
+        assert_eq!(requests.len(), 2);
+        for req in requests {
+            assert_eq!(req.query("list-type"), Some("2"));
+            assert_eq!(req.query("encoding-type"), Some("url"));
         }
+        assert_eq!(requests[0].query("continuation-token"), None);
+        assert_eq!(requests[1].query("continuation-token"), Some("page-2"));
+    }
+
+    #[test]
+    fn list_objects_v2_iter_follows_the_pages() {
+        let (url, requests) = list_server();
+        let oss = test_server::client(&url);
+
+        let entries = oss
+            .list_objects_v2_iter(None, list_resources())
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_two_pages_listed(&entries, &requests.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn list_objects_v2_stream_follows_the_pages() {
+        let (url, requests) = list_server();
+        let oss = test_server::client(&url);
+
+        let entries = oss
+            .list_objects_v2_stream(None, list_resources())
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_two_pages_listed(&entries, &requests.lock().unwrap());
+    }
+
+    #[test]
+    fn list_objects_v2_decodes_url_encoding() {
+        let list = ListObjectsV2::from_xml(
+            "<ListBucketResult><Name>bucket</Name><Prefix>a%2F</Prefix>\
+             <StartAfter>a%2F%E4%B8%AD</StartAfter><MaxKeys>1</MaxKeys>\
+             <EncodingType>url</EncodingType><IsTruncated>true</IsTruncated>\
+             <NextContinuationToken>a%2Fb%3D</NextContinuationToken>\
+             </ListBucketResult>",
+        )
+        .unwrap();
+        assert_eq!(list.prefix(), "a/");
+        assert_eq!(list.start_after(), "a/\u{4e2d}");
+        assert_eq!(list.next_continuation_token(), "a/b=");
+        assert!(list.contents().is_empty());
     }
 }
@@ -0,0 +1,597 @@
+//! Typed options of object requests.
+//!
+//! The options are turned into the headers and resources taken by the
+//! methods of `ObjectAPI` and `AsyncObjectAPI` with `into_parts`, and raw
+//! headers or resources can still be added with `header` and `resource`.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::time::SystemTime;
+
+pub type Headers = HashMap<String, String>;
+pub type Resources = HashMap<String, Option<String>>;
+
+/// Implement `as_str`, `Display`, `Serialize` and `Deserialize` of an enum
+/// from the string of each variant. With a catch-all variant, e.g.
+/// `StorageClass(Other)`, unknown strings are kept in it and `From<&str>` is
+/// implemented, otherwise deserializing them fails.
+macro_rules! string_enum {
+    ($name:ident { $($variant:ident => $s:literal),+ $(,)? }) => {
+        impl $name {
+            pub fn as_str(&self) -> &'static str {
+                match self {
+                    $($name::$variant => $s,)+
+                }
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                match <String as serde::Deserialize>::deserialize(deserializer)?.as_str() {
+                    $($s => Ok($name::$variant),)+
+                    other => Err(serde::de::Error::unknown_variant(other, &[$($s),+])),
+                }
+            }
+        }
+
+        string_enum!(@text $name);
+    };
+    ($name:ident($other:ident) { $($variant:ident => $s:literal),+ $(,)? }) => {
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $($name::$variant => $s,)+
+                    $name::$other(s) => s,
+                }
+            }
+        }
+
+        impl From<&str> for $name {
+            fn from(s: &str) -> Self {
+                match s {
+                    $($s => $name::$variant,)+
+                    other => $name::$other(other.to_string()),
+                }
+            }
+        }
+
+        impl<'de> serde::Deserialize<'de> for $name {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                Ok($name::from(
+                    <String as serde::Deserialize>::deserialize(deserializer)?.as_str(),
+                ))
+            }
+        }
+
+        string_enum!(@text $name);
+    };
+    (@text $name:ident) => {
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+
+        // written as text, quick-xml would make a unit variant an element
+        impl serde::Serialize for $name {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.as_str())
+            }
+        }
+    };
+}
+pub(crate) use string_enum;
+
+/// Canned ACL of an object, sent in `x-oss-object-acl`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ObjectAcl {
+    /// Inherit the ACL of the bucket.
+    Default,
+    Private,
+    PublicRead,
+    PublicReadWrite,
+}
+
+impl ObjectAcl {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ObjectAcl::Default => "default",
+            ObjectAcl::Private => "private",
+            ObjectAcl::PublicRead => "public-read",
+            ObjectAcl::PublicReadWrite => "public-read-write",
+        }
+    }
+}
+
+impl fmt::Display for ObjectAcl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StorageClass {
+    Standard,
+    IA,
+    Archive,
+    ColdArchive,
+    DeepColdArchive,
+    Other(String),
+}
+
+string_enum! {
+    StorageClass(Other) {
+        Standard => "Standard",
+        IA => "IA",
+        Archive => "Archive",
+        ColdArchive => "ColdArchive",
+        DeepColdArchive => "DeepColdArchive",
+    }
+}
+
+/// Options of GetObject.
+///
+/// # Examples
+///
+/// ```
+/// use oss_rust_sdk::options::GetObjectOptions;
+///
+/// let (headers, resources) = GetObjectOptions::new()
+///     .range(0, Some(1023))
+///     .if_none_match("\"5B3C1A2E053D763E1B002CC607C5A0FE\"")
+///     .response_content_type("text/plain")
+///     .into_parts();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct GetObjectOptions {
+    headers: Headers,
+    resources: Resources,
+}
+
+impl GetObjectOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bytes `start` to `end` included, or to the end of the object.
+    pub fn range(self, start: u64, end: Option<u64>) -> Self {
+        let range = match end {
+            Some(end) => format!("bytes={}-{}", start, end),
+            None => format!("bytes={}-", start),
+        };
+        self.header("Range", range)
+    }
+
+    pub fn if_match<S: Into<String>>(self, e_tag: S) -> Self {
+        self.header("If-Match", e_tag)
+    }
+
+    pub fn if_none_match<S: Into<String>>(self, e_tag: S) -> Self {
+        self.header("If-None-Match", e_tag)
+    }
+
+    pub fn if_modified_since(self, time: SystemTime) -> Self {
+        self.header("If-Modified-Since", httpdate::fmt_http_date(time))
+    }
+
+    pub fn if_unmodified_since(self, time: SystemTime) -> Self {
+        self.header("If-Unmodified-Since", httpdate::fmt_http_date(time))
+    }
+
+    pub fn response_content_type<S: Into<String>>(self, value: S) -> Self {
+        self.resource("response-content-type", Some(value))
+    }
+
+    pub fn response_content_language<S: Into<String>>(self, value: S) -> Self {
+        self.resource("response-content-language", Some(value))
+    }
+
+    pub fn response_expires<S: Into<String>>(self, value: S) -> Self {
+        self.resource("response-expires", Some(value))
+    }
+
+    pub fn response_cache_control<S: Into<String>>(self, value: S) -> Self {
+        self.resource("response-cache-control", Some(value))
+    }
+
+    pub fn response_content_disposition<S: Into<String>>(self, value: S) -> Self {
+        self.resource("response-content-disposition", Some(value))
+    }
+
+    pub fn response_content_encoding<S: Into<String>>(self, value: S) -> Self {
+        self.resource("response-content-encoding", Some(value))
+    }
+
+    /// Image or document processing, e.g. `image/resize,w_100`.
+    pub fn process<S: Into<String>>(self, process: S) -> Self {
+        self.resource("x-oss-process", Some(process))
+    }
+
+    /// Get a previous version of the object.
+    pub fn version_id<S: Into<String>>(self, version_id: S) -> Self {
+        self.resource("versionId", Some(version_id))
+    }
+
+    /// Any other header.
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    /// Any other resource, i.e. query parameter.
+    pub fn resource<K: Into<String>, V: Into<String>>(mut self, key: K, value: Option<V>) -> Self {
+        self.resources.insert(key.into(), value.map(Into::into));
+        self
+    }
+
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    pub fn into_parts(self) -> (Headers, Resources) {
+        (self.headers, self.resources)
+    }
+}
+
+/// Options of the requests on an existing object: HeadObject, DeleteObject,
+/// GetObjectACL and the object tagging requests.
+///
+/// # Examples
+///
+/// ```
+/// use oss_rust_sdk::options::ObjectOptions;
+///
+/// let (_, resources) = ObjectOptions::new().version_id("CAEQ0").into_parts();
+/// assert_eq!(resources["versionId"].as_deref(), Some("CAEQ0"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ObjectOptions {
+    headers: Headers,
+    resources: Resources,
+}
+
+impl ObjectOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Apply to a previous version of the object.
+    pub fn version_id<S: Into<String>>(self, version_id: S) -> Self {
+        self.resource("versionId", Some(version_id))
+    }
+
+    /// Any other header.
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    /// Any other resource, i.e. query parameter.
+    pub fn resource<K: Into<String>, V: Into<String>>(mut self, key: K, value: Option<V>) -> Self {
+        self.resources.insert(key.into(), value.map(Into::into));
+        self
+    }
+
+    pub fn headers(&self) -> &Headers {
+        &self.headers
+    }
+
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    pub fn into_parts(self) -> (Headers, Resources) {
+        (self.headers, self.resources)
+    }
+}
+
+/// Options of PutObject, also accepted by the other uploads.
+///
+/// # Examples
+///
+/// ```
+/// use oss_rust_sdk::options::{ObjectAcl, PutObjectOptions, StorageClass};
+///
+/// let (headers, resources) = PutObjectOptions::new()
+///     .content_type("image/png")
+///     .acl(ObjectAcl::Private)
+///     .storage_class(StorageClass::IA)
+///     .meta("origin", "camera")
+///     .tag("project", "demo")
+///     .forbid_overwrite(true)
+///     .into_parts();
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct PutObjectOptions {
+    headers: Headers,
+    resources: Resources,
+    tags: Vec<(String, String)>,
+}
+
+impl PutObjectOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn content_type<S: Into<String>>(self, value: S) -> Self {
+        self.header("Content-Type", value)
+    }
+
+    pub fn cache_control<S: Into<String>>(self, value: S) -> Self {
+        self.header("Cache-Control", value)
+    }
+
+    pub fn content_disposition<S: Into<String>>(self, value: S) -> Self {
+        self.header("Content-Disposition", value)
+    }
+
+    pub fn content_encoding<S: Into<String>>(self, value: S) -> Self {
+        self.header("Content-Encoding", value)
+    }
+
+    pub fn content_language<S: Into<String>>(self, value: S) -> Self {
+        self.header("Content-Language", value)
+    }
+
+    pub fn expires<S: Into<String>>(self, value: S) -> Self {
+        self.header("Expires", value)
+    }
+
+    /// Base64 encoded MD5 of the body, checked by OSS.
+    pub fn content_md5<S: Into<String>>(self, value: S) -> Self {
+        self.header("Content-MD5", value)
+    }
+
+    pub fn acl(self, acl: ObjectAcl) -> Self {
+        self.header("x-oss-object-acl", acl.as_str())
+    }
+
+    pub fn storage_class(self, storage_class: StorageClass) -> Self {
+        self.header("x-oss-storage-class", storage_class.as_str())
+    }
+
+    /// User metadata, sent as `x-oss-meta-{key}`.
+    pub fn meta<K: AsRef<str>, V: Into<String>>(self, key: K, value: V) -> Self {
+        let key = format!("x-oss-meta-{}", key.as_ref());
+        self.header(key, value)
+    }
+
+    /// Add a tag, all of them are sent in `x-oss-tagging`.
+    pub fn tag<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// Server side encryption, `AES256`, `KMS` or `SM4`.
+    pub fn server_side_encryption<S: Into<String>>(self, algorithm: S) -> Self {
+        self.header("x-oss-server-side-encryption", algorithm)
+    }
+
+    /// KMS key of the `KMS` server side encryption.
+    pub fn server_side_encryption_key_id<S: Into<String>>(self, key_id: S) -> Self {
+        self.header("x-oss-server-side-encryption-key-id", key_id)
+    }
+
+    /// Fail with `FileAlreadyExists` instead of replacing an existing object.
+    pub fn forbid_overwrite(self, forbid: bool) -> Self {
+        self.header("x-oss-forbid-overwrite", forbid.to_string())
+    }
+
+    /// Any other header.
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    /// Any other resource, i.e. query parameter.
+    pub fn resource<K: Into<String>, V: Into<String>>(mut self, key: K, value: Option<V>) -> Self {
+        self.resources.insert(key.into(), value.map(Into::into));
+        self
+    }
+
+    pub fn into_parts(self) -> (Headers, Resources) {
+        let mut headers = self.headers;
+        if !self.tags.is_empty() {
+            headers.insert("x-oss-tagging".to_string(), encode_tags(&self.tags));
+        }
+        (headers, self.resources)
+    }
+}
+
+/// Whether a copy keeps the metadata or tags of the source, or replaces them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Directive {
+    Copy,
+    Replace,
+}
+
+impl Directive {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Directive::Copy => "COPY",
+            Directive::Replace => "REPLACE",
+        }
+    }
+}
+
+impl fmt::Display for Directive {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Options of CopyObject.
+///
+/// Setting tags replaces the ones of the source, unless a tagging directive
+/// is set explicitly.
+///
+/// # Examples
+///
+/// ```
+/// use oss_rust_sdk::options::{CopyObjectOptions, Directive, StorageClass};
+///
+/// let (headers, resources) = CopyObjectOptions::new()
+///     .metadata_directive(Directive::Replace)
+///     .content_type("text/plain")
+///     .storage_class(StorageClass::Archive)
+///     .tag("retention", "1y")
+///     .into_parts();
+/// assert_eq!(headers["x-oss-tagging-directive"], "REPLACE");
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CopyObjectOptions {
+    headers: Headers,
+    resources: Resources,
+    tags: Vec<(String, String)>,
+    source_version_id: Option<String>,
+}
+
+impl CopyObjectOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Copy a previous version of the source, e.g. to restore it.
+    pub fn source_version_id<S: Into<String>>(mut self, version_id: S) -> Self {
+        self.source_version_id = Some(version_id.into());
+        self
+    }
+
+    pub fn copy_source_if_match<S: Into<String>>(self, e_tag: S) -> Self {
+        self.header("x-oss-copy-source-if-match", e_tag)
+    }
+
+    pub fn copy_source_if_none_match<S: Into<String>>(self, e_tag: S) -> Self {
+        self.header("x-oss-copy-source-if-none-match", e_tag)
+    }
+
+    pub fn copy_source_if_modified_since(self, time: SystemTime) -> Self {
+        self.header(
+            "x-oss-copy-source-if-modified-since",
+            httpdate::fmt_http_date(time),
+        )
+    }
+
+    pub fn copy_source_if_unmodified_since(self, time: SystemTime) -> Self {
+        self.header(
+            "x-oss-copy-source-if-unmodified-since",
+            httpdate::fmt_http_date(time),
+        )
+    }
+
+    /// Keep the metadata of the source, the default, or use the one of the request.
+    pub fn metadata_directive(self, directive: Directive) -> Self {
+        self.header("x-oss-metadata-directive", directive.as_str())
+    }
+
+    /// Keep the tags of the source, the default, or use the ones of the request.
+    pub fn tagging_directive(self, directive: Directive) -> Self {
+        self.header("x-oss-tagging-directive", directive.as_str())
+    }
+
+    /// Only applied with the `Replace` metadata directive.
+    pub fn content_type<S: Into<String>>(self, value: S) -> Self {
+        self.header("Content-Type", value)
+    }
+
+    pub fn acl(self, acl: ObjectAcl) -> Self {
+        self.header("x-oss-object-acl", acl.as_str())
+    }
+
+    pub fn storage_class(self, storage_class: StorageClass) -> Self {
+        self.header("x-oss-storage-class", storage_class.as_str())
+    }
+
+    /// User metadata, only applied with the `Replace` metadata directive.
+    pub fn meta<K: AsRef<str>, V: Into<String>>(self, key: K, value: V) -> Self {
+        let key = format!("x-oss-meta-{}", key.as_ref());
+        self.header(key, value)
+    }
+
+    /// Add a tag, all of them are sent in `x-oss-tagging`.
+    pub fn tag<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.tags.push((key.into(), value.into()));
+        self
+    }
+
+    /// Fail with `FileAlreadyExists` instead of replacing an existing object.
+    pub fn forbid_overwrite(self, forbid: bool) -> Self {
+        self.header("x-oss-forbid-overwrite", forbid.to_string())
+    }
+
+    /// Any other header.
+    pub fn header<K: Into<String>, V: Into<String>>(mut self, key: K, value: V) -> Self {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    /// Any other resource, i.e. query parameter.
+    pub fn resource<K: Into<String>, V: Into<String>>(mut self, key: K, value: Option<V>) -> Self {
+        self.resources.insert(key.into(), value.map(Into::into));
+        self
+    }
+
+    /// `x-oss-copy-source` of `src`, with the version to copy if any.
+    pub fn copy_source(&self, src: &str) -> String {
+        match &self.source_version_id {
+            Some(version_id) => format!("{}?versionId={}", src, version_id),
+            None => src.to_string(),
+        }
+    }
+
+    pub fn into_parts(self) -> (Headers, Resources) {
+        let mut headers = self.headers;
+        if !self.tags.is_empty() {
+            headers.insert("x-oss-tagging".to_string(), encode_tags(&self.tags));
+            headers
+                .entry("x-oss-tagging-directive".to_string())
+                .or_insert_with(|| Directive::Replace.as_str().to_string());
+        }
+        (headers, self.resources)
+    }
+}
+
+/// Tags as the url encoded query string expected in `x-oss-tagging`.
+pub(crate) fn encode_tags(tags: &[(String, String)]) -> String {
+    tags.iter()
+        .map(|(k, v)| format!("{}={}", urlencoding::encode(k), urlencoding::encode(v)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::versioning::{VersioningConfiguration, VersioningStatus};
+
+    #[test]
+    fn string_enums_are_written_as_text() {
+        let config = VersioningConfiguration::new(VersioningStatus::Suspended);
+        let xml = config.to_xml().unwrap();
+        assert_eq!(
+            xml,
+            "<VersioningConfiguration><Status>Suspended</Status></VersioningConfiguration>"
+        );
+        assert_eq!(VersioningConfiguration::from_xml(&xml).unwrap(), config);
+        assert_eq!(VersioningStatus::Enabled.to_string(), "Enabled");
+
+        let err = VersioningConfiguration::from_xml(
+            "<VersioningConfiguration><Status>Disabled</Status></VersioningConfiguration>",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("unknown variant"), "{}", err);
+    }
+
+    #[test]
+    fn unknown_storage_class_is_kept() {
+        let class: StorageClass = quick_xml::de::from_str("<S>Glacier</S>").unwrap();
+        assert_eq!(class, StorageClass::Other("Glacier".to_string()));
+        assert_eq!(class.as_str(), "Glacier");
+        assert_eq!(StorageClass::from("IA"), StorageClass::IA);
+        assert_eq!(StorageClass::ColdArchive.to_string(), "ColdArchive");
+    }
+}
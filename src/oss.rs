@@ -1,18 +1,22 @@
 use chrono::prelude::*;
 use reqwest::header::{HeaderMap, DATE};
-use reqwest::Client;
+use reqwest::{tls, Certificate, Client, Proxy};
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::str;
+use std::sync::{Arc, OnceLock};
 use std::time::{Duration, SystemTime};
 
 use crate::errors::ObjectError;
 
 use super::auth::*;
+use super::credentials::{Credentials, CredentialsProvider, StaticProvider};
 use super::errors::Error;
+use super::options::StorageClass;
+use super::retry::RetryPolicy;
 use super::utils::*;
 
-const RESOURCES: [&str; 50] = [
+const RESOURCES: [&str; 56] = [
     "acl",
     "uploads",
     "location",
@@ -63,22 +67,121 @@ const RESOURCES: [&str; 50] = [
     "restore",
     "callback",
     "callback-var",
+    "stat",
+    "policy",
+    "versioning",
+    "versions",
+    "versionId",
+    "continuation-token",
 ];
 
+/// A client of a bucket. Clones share the http clients and the credentials
+/// provider.
+///
+/// The blocking API sends its requests from the thread of a blocking client,
+/// started by the first blocking call. Dropping the last clone of a client
+/// which made blocking calls stops that thread and waits for it, blocking the
+/// dropping thread: when the blocking API is used from async code, drop the
+/// client outside of the runtime, e.g. in `spawn_blocking`, or keep separate
+/// clients for the async and the blocking API.
 #[derive(Clone, Debug)]
 pub struct OSS<'a> {
-    key_id: Cow<'a, str>,
-    key_secret: Cow<'a, str>,
+    credentials_provider: Arc<dyn CredentialsProvider>,
     endpoint: Cow<'a, str>,
     bucket: Cow<'a, str>,
+    region: Cow<'a, str>,
+    signature_version: SignatureVersion,
+    retry_policy: Option<RetryPolicy>,
+    http_config: HttpConfig,
 
     pub(crate) http_client: Client,
+    /// Built on the first blocking call, so that async code which never uses
+    /// it does not start its thread.
+    blocking_http_client: Arc<OnceLock<reqwest::blocking::Client>>,
+}
+
+/// Settings of the http clients, shared by the async and the blocking one.
+#[derive(Clone, Debug, Default)]
+struct HttpConfig {
+    pool_max_idle_per_host: Option<usize>,
+    timeout: Option<Duration>,
+    proxy: Option<Proxy>,
+    root_certificates: Vec<Certificate>,
+    danger_accept_invalid_certs: bool,
+    min_tls_version: Option<tls::Version>,
+}
+
+/// Apply the settings of an `HttpConfig` to a builder of the async or the
+/// blocking client, whose methods have the same names but no common trait.
+macro_rules! configure {
+    ($config:expr, $builder:expr) => {{
+        let config = $config;
+        let mut builder = $builder;
+        if let Some(timeout) = config.timeout {
+            builder = builder.timeout(timeout);
+        }
+        if let Some(max_per_host) = config.pool_max_idle_per_host {
+            builder = builder.pool_max_idle_per_host(max_per_host);
+        }
+        if let Some(proxy) = &config.proxy {
+            builder = builder.proxy(proxy.clone());
+        }
+        for certificate in &config.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        if let Some(version) = config.min_tls_version {
+            builder = builder.min_tls_version(version);
+        }
+        builder.danger_accept_invalid_certs(config.danger_accept_invalid_certs)
+    }};
+}
+
+impl HttpConfig {
+    fn build(&self) -> Client {
+        configure!(self, Client::builder())
+            .build()
+            .expect("Build http client failed")
+    }
+
+    fn build_blocking(&self) -> reqwest::blocking::Client {
+        configure!(self, reqwest::blocking::Client::builder())
+            .build()
+            .expect("Build blocking http client failed")
+    }
+}
+
+/// The algorithm used to sign requests.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SignatureVersion {
+    /// The legacy HMAC-SHA1 `OSS AccessKeyId:Signature` scheme.
+    #[default]
+    V1,
+    /// OSS4-HMAC-SHA256, scoped to the region of the client.
+    V4,
 }
 
 #[derive(Default)]
 pub struct Options {
     pub pool_max_idle_per_host: Option<usize>,
     pub timeout: Option<Duration>,
+    pub signature_version: SignatureVersion,
+    /// Region used by the V4 signature, e.g. `cn-hangzhou`. Derived from the
+    /// endpoint when not set.
+    pub region: Option<String>,
+    /// Retry failed requests, requests are attempted once when not set.
+    pub retry_policy: Option<RetryPolicy>,
+    /// Proxy of both the async and the blocking client.
+    pub proxy: Option<Proxy>,
+    /// Root certificates trusted on top of the system ones, e.g. of a
+    /// private CA in front of the endpoint.
+    pub root_certificates: Vec<Certificate>,
+    /// Accept invalid certificates, including self-signed and expired ones.
+    /// Anyone on the path can then read and change the requests.
+    pub danger_accept_invalid_certs: bool,
+    /// Lowest TLS version accepted, e.g. `tls::Version::TLS_1_2`. The
+    /// `native-tls` backend can not require TLS 1.3, creating the client then
+    /// panics; use `rustls-tls` for it.
+    pub min_tls_version: Option<tls::Version>,
 }
 
 impl<'a> OSS<'a> {
@@ -89,25 +192,62 @@ impl<'a> OSS<'a> {
         Self::new_with_opts(key_id, key_secret, endpoint, bucket, Default::default())
     }
 
+    /// Create a client from STS temporary credentials, every request will carry
+    /// the `x-oss-security-token`.
+    pub fn new_with_sts<S>(
+        key_id: S,
+        key_secret: S,
+        security_token: S,
+        endpoint: S,
+        bucket: S,
+        opts: Options,
+    ) -> Self
+    where
+        S: Into<Cow<'a, str>>,
+    {
+        let credentials = Credentials::new(key_id.into(), key_secret.into())
+            .with_security_token(security_token.into());
+        Self::new_with_provider(StaticProvider::new(credentials), endpoint, bucket, opts)
+    }
+
     pub fn new_with_opts<S>(key_id: S, key_secret: S, endpoint: S, bucket: S, opts: Options) -> Self
     where
         S: Into<Cow<'a, str>>,
     {
-        let mut builder = Client::builder();
-        if let Some(timeout) = opts.timeout {
-            builder = builder.timeout(timeout);
-        }
-        if let Some(max_per_host) = opts.pool_max_idle_per_host {
-            builder = builder.pool_max_idle_per_host(max_per_host);
-        }
+        let credentials = Credentials::new(key_id.into(), key_secret.into());
+        Self::new_with_provider(StaticProvider::new(credentials), endpoint, bucket, opts)
+    }
 
-        let http_client = builder.build().expect("Build http client failed");
+    /// Create a client which asks `provider` for the credentials of every request.
+    pub fn new_with_provider<P, S>(provider: P, endpoint: S, bucket: S, opts: Options) -> Self
+    where
+        P: CredentialsProvider + 'static,
+        S: Into<Cow<'a, str>>,
+    {
+        let http_config = HttpConfig {
+            pool_max_idle_per_host: opts.pool_max_idle_per_host,
+            timeout: opts.timeout,
+            proxy: opts.proxy,
+            root_certificates: opts.root_certificates,
+            danger_accept_invalid_certs: opts.danger_accept_invalid_certs,
+            min_tls_version: opts.min_tls_version,
+        };
+        let http_client = http_config.build();
+        let endpoint = endpoint.into();
+        let region = match opts.region {
+            Some(region) => region.into(),
+            None => region_from_endpoint(&endpoint).into(),
+        };
         OSS {
-            key_id: key_id.into(),
-            key_secret: key_secret.into(),
-            endpoint: endpoint.into(),
+            credentials_provider: Arc::new(provider),
+            endpoint,
             bucket: bucket.into(),
+            region,
+            signature_version: opts.signature_version,
+            retry_policy: opts.retry_policy,
+            http_config,
             http_client,
+            blocking_http_client: Arc::new(OnceLock::new()),
         }
     }
 
@@ -119,18 +259,90 @@ impl<'a> OSS<'a> {
         &self.endpoint
     }
 
-    pub fn key_id(&self) -> &str {
-        &self.key_id
+    /// Current credentials from the provider of this client.
+    pub fn credentials(&self) -> Result<Credentials, Error> {
+        self.credentials_provider.credentials()
+    }
+
+    /// `credentials` for the async API, which does not block the runtime
+    /// while a provider fetches remote credentials.
+    pub async fn credentials_async(&self) -> Result<Credentials, Error> {
+        self.credentials_provider.credentials_async().await
+    }
+
+    /// AccessKey id of the current credentials, empty when the provider fails.
+    #[deprecated(since = "0.10.0", note = "use `credentials()`")]
+    pub fn key_id(&self) -> String {
+        self.credentials()
+            .map(|credentials| credentials.access_key_id().to_string())
+            .unwrap_or_default()
+    }
+
+    /// AccessKey secret of the current credentials, empty when the provider fails.
+    #[deprecated(since = "0.10.0", note = "use `credentials()`")]
+    pub fn key_secret(&self) -> String {
+        self.credentials()
+            .map(|credentials| credentials.access_key_secret().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn region(&self) -> &str {
+        &self.region
+    }
+
+    pub fn signature_version(&self) -> SignatureVersion {
+        self.signature_version
     }
 
-    pub fn key_secret(&self) -> &str {
-        &self.key_secret
+    pub fn retry_policy(&self) -> Option<&RetryPolicy> {
+        self.retry_policy.as_ref()
     }
 
     pub fn set_bucket(&mut self, bucket: &'a str) {
         self.bucket = bucket.into()
     }
 
+    pub fn set_retry_policy(&mut self, retry_policy: Option<RetryPolicy>) {
+        self.retry_policy = retry_policy
+    }
+
+    /// A copy of the client which owns its strings, e.g. to be moved into a task.
+    pub(crate) fn to_owned_client(&self) -> OSS<'static> {
+        OSS {
+            credentials_provider: self.credentials_provider.clone(),
+            endpoint: Cow::Owned(self.endpoint.to_string()),
+            bucket: Cow::Owned(self.bucket.to_string()),
+            region: Cow::Owned(self.region.to_string()),
+            signature_version: self.signature_version,
+            retry_policy: self.retry_policy.clone(),
+            http_config: self.http_config.clone(),
+            http_client: self.http_client.clone(),
+            blocking_http_client: self.blocking_http_client.clone(),
+        }
+    }
+
+    /// The pooled client of the blocking API, configured like `http_client`.
+    pub(crate) fn blocking_http_client(&self) -> &reqwest::blocking::Client {
+        self.blocking_http_client
+            .get_or_init(|| self.http_config.build_blocking())
+    }
+
+    /// Replace the temporary credentials, e.g. after the STS token is renewed.
+    pub fn set_sts<S>(&mut self, key_id: S, key_secret: S, security_token: S)
+    where
+        S: Into<String>,
+    {
+        let credentials = Credentials::new(key_id, key_secret).with_security_token(security_token);
+        self.set_credentials_provider(StaticProvider::new(credentials));
+    }
+
+    pub fn set_credentials_provider<P>(&mut self, provider: P)
+    where
+        P: CredentialsProvider + 'static,
+    {
+        self.credentials_provider = Arc::new(provider);
+    }
+
     pub fn host(&self, bucket: &str, object: &str, resources_str: &str) -> String {
         if self.endpoint.starts_with("https") {
             format!(
@@ -151,11 +363,31 @@ impl<'a> OSS<'a> {
         }
     }
 
+    /// Url of a service level request, which is sent to the endpoint itself.
+    pub fn service_host(&self, resources_str: &str) -> String {
+        let endpoint = if self.endpoint.starts_with("http") {
+            self.endpoint.to_string()
+        } else {
+            format!("http://{}", self.endpoint)
+        };
+        if resources_str.is_empty() {
+            endpoint
+        } else {
+            format!("{}/?{}", endpoint.trim_end_matches('/'), resources_str)
+        }
+    }
+
     pub fn date(&self) -> String {
         let now: DateTime<Utc> = Utc::now();
         now.format("%a, %d %b %Y %T GMT").to_string()
     }
 
+    /// Timestamp in the ISO 8601 basic format used by the V4 signature.
+    pub fn date_time(&self) -> String {
+        let now: DateTime<Utc> = Utc::now();
+        now.format("%Y%m%dT%H%M%SZ").to_string()
+    }
+
     pub fn get_resources_str<S>(&self, params: &HashMap<S, Option<S>>) -> String
     where
         S: AsRef<str>,
@@ -164,7 +396,7 @@ impl<'a> OSS<'a> {
             .iter()
             .filter(|(k, _)| RESOURCES.contains(&k.as_ref()))
             .collect();
-        resources.sort_by(|a, b| a.0.as_ref().to_string().cmp(&b.0.as_ref().to_string()));
+        resources.sort_by_key(|a| a.0.as_ref().to_string());
         let mut result = String::new();
         for (k, v) in resources {
             if !result.is_empty() {
@@ -179,21 +411,40 @@ impl<'a> OSS<'a> {
         result
     }
 
+    /// Query string of `build_request`, keys and values are written as given
+    /// so they have to be encoded by the caller.
     pub fn get_params_str<S>(&self, params: &HashMap<S, Option<S>>) -> String
     where
         S: AsRef<str>,
+    {
+        self.params_str(params, |s| s.to_string())
+    }
+
+    /// Query string of the requests sent by the client, with percent-encoded
+    /// keys and values.
+    pub(crate) fn get_encoded_params_str<S>(&self, params: &HashMap<S, Option<S>>) -> String
+    where
+        S: AsRef<str>,
+    {
+        self.params_str(params, |s| uri_encode(s, true))
+    }
+
+    fn params_str<S, F>(&self, params: &HashMap<S, Option<S>>, encode: F) -> String
+    where
+        S: AsRef<str>,
+        F: Fn(&str) -> String,
     {
         let mut resources: Vec<(&S, &Option<S>)> = params.iter().collect();
-        resources.sort_by(|a, b| a.0.as_ref().to_string().cmp(&b.0.as_ref().to_string()));
+        resources.sort_by_key(|a| a.0.as_ref().to_string());
         let mut result = String::new();
         for (k, v) in resources {
             if !result.is_empty() {
                 result += "&";
             }
+            result += &encode(k.as_ref());
             if let Some(vv) = v {
-                result += &format!("{}={}", k.as_ref().to_owned(), vv.as_ref());
-            } else {
-                result += k.as_ref();
+                result += "=";
+                result += &encode(vv.as_ref());
             }
         }
         result
@@ -214,35 +465,111 @@ impl<'a> OSS<'a> {
         R: Into<Option<HashMap<S2, Option<S2>>>>,
     {
         let object_name = object_name.as_ref();
-        let (resources_str, params_str) = if let Some(r) = resources.into() {
-            (self.get_resources_str(&r), self.get_params_str(&r))
+        let resources = resources.into();
+        let params_str = if let Some(r) = &resources {
+            self.get_params_str(r)
         } else {
-            (String::new(), String::new())
+            String::new()
         };
 
         let host = self.host(self.bucket(), object_name, &params_str);
-        let date = self.date();
         let mut headers = if let Some(h) = headers.into() {
             to_headers(h)?
         } else {
             HeaderMap::new()
         };
-        headers.insert(DATE, date.parse()?);
-        let authorization = self.oss_sign(
+        self.sign_headers(
+            &self.credentials()?,
             req_type.as_str(),
-            self.key_id(),
-            self.key_secret(),
             self.bucket(),
             object_name,
-            &resources_str,
-            &headers,
-        );
-        headers.insert("Authorization", authorization.parse()?);
+            resources.as_ref(),
+            &mut headers,
+        )?;
 
         Ok((host, headers))
     }
+
+    /// Add the date and `Authorization` headers required by the configured
+    /// signature version.
+    pub(crate) fn sign_headers<S>(
+        &self,
+        credentials: &Credentials,
+        verb: &str,
+        bucket: &str,
+        object_name: &str,
+        resources: Option<&HashMap<S, Option<S>>>,
+        headers: &mut HeaderMap,
+    ) -> Result<(), Error>
+    where
+        S: AsRef<str>,
+    {
+        if let Some(token) = credentials.security_token() {
+            headers.insert("x-oss-security-token", token.parse()?);
+        }
+        let authorization = match self.signature_version {
+            SignatureVersion::V1 => {
+                let resources_str = if let Some(r) = resources {
+                    self.get_resources_str(r)
+                } else {
+                    String::new()
+                };
+                headers.insert(DATE, self.date().parse()?);
+                self.oss_sign(
+                    verb,
+                    credentials.access_key_id(),
+                    credentials.access_key_secret(),
+                    bucket,
+                    object_name,
+                    &resources_str,
+                    headers,
+                )
+            }
+            SignatureVersion::V4 => {
+                let params: Vec<(&str, Option<&str>)> = resources
+                    .map(|r| {
+                        r.iter()
+                            .map(|(k, v)| (k.as_ref(), v.as_ref().map(|v| v.as_ref())))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                let date_time = self.date_time();
+                headers.insert("x-oss-date", date_time.parse()?);
+                if !headers.contains_key("x-oss-content-sha256") {
+                    headers.insert("x-oss-content-sha256", UNSIGNED_PAYLOAD.parse()?);
+                }
+                self.oss_sign_v4(
+                    verb,
+                    credentials.access_key_id(),
+                    credentials.access_key_secret(),
+                    self.region(),
+                    bucket,
+                    object_name,
+                    &params,
+                    headers,
+                    &date_time,
+                )
+            }
+        };
+        headers.insert("Authorization", authorization.parse()?);
+        Ok(())
+    }
 }
 
+/// Guess the region from an endpoint such as `https://oss-cn-hangzhou.aliyuncs.com`
+/// or `oss-cn-hangzhou-internal.aliyuncs.com`.
+fn region_from_endpoint(endpoint: &str) -> String {
+    let host = endpoint
+        .trim_start_matches("https://")
+        .trim_start_matches("http://");
+    let label = host.split('.').next().unwrap_or_default();
+    label
+        .trim_start_matches("oss-")
+        .trim_end_matches("-internal")
+        .to_string()
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum RequestType {
     Get,
     Put,
@@ -263,7 +590,7 @@ impl RequestType {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ObjectMeta {
     /// The last modified time
     pub last_modified: SystemTime,
@@ -271,6 +598,27 @@ pub struct ObjectMeta {
     pub size: usize,
     /// 128-bits RFC 1864 MD5. This field only presents in normal file. Multipart and append-able file will have empty md5.
     pub md5: String,
+    pub e_tag: String,
+    pub content_type: Option<String>,
+    pub content_encoding: Option<String>,
+    pub content_disposition: Option<String>,
+    pub cache_control: Option<String>,
+    pub expires: Option<String>,
+    pub storage_class: Option<StorageClass>,
+    /// `Normal`, `Multipart`, `Appendable` or `Symlink`.
+    pub object_type: Option<String>,
+    pub version_id: Option<String>,
+    /// CRC-64/ECMA-182 of the whole object.
+    pub crc64: Option<u64>,
+    pub server_side_encryption: Option<String>,
+    pub server_side_encryption_key_id: Option<String>,
+    /// Raw `x-oss-restore` of archived objects.
+    pub restore: Option<String>,
+    pub tagging_count: Option<usize>,
+    /// Position of the next append to an appendable object.
+    pub next_append_position: Option<u64>,
+    /// `x-oss-meta-*` headers, keyed without the prefix.
+    pub user_meta: HashMap<String, String>,
 }
 
 impl ObjectMeta {
@@ -283,35 +631,116 @@ impl ObjectMeta {
                         msg: format!(
                             "can not find {} in head response, response header: {:?}",
                             key, header
-                        )
-                        .into(),
+                        ),
                     })
                 })?
                 .to_str()
                 .map_err(|_| {
                     Error::Object(ObjectError::HeadError {
-                        msg: format!("header entry {} contains invalid ASCII code", key).into(),
+                        msg: format!("header entry {} contains invalid ASCII code", key),
                     })
                 })?;
             Ok(value)
         };
+        let optional = |key: &str| -> Option<String> {
+            header
+                .get(key)
+                .map(|v| String::from_utf8_lossy(v.as_bytes()).into_owned())
+        };
+        let number = |key: &str| optional(key).and_then(|v| v.parse().ok());
 
         let last_modified = httpdate::parse_http_date(getter("Last-Modified")?).map_err(|e| {
             Error::Object(ObjectError::HeadError {
-                msg: format!("cannot parse to system time: {}", e).into(),
+                msg: format!("cannot parse to system time: {}", e),
             })
         })?;
         let size = getter("Content-Length")?.parse().map_err(|e| {
             Error::Object(ObjectError::HeadError {
-                msg: format!("cannot parse to number: {}", e).into(),
+                msg: format!("cannot parse to number: {}", e),
             })
         })?;
-        let md5 = getter("Content-Md5")?.to_string();
+
+        let user_meta = header
+            .iter()
+            .filter_map(|(k, v)| {
+                k.as_str().strip_prefix("x-oss-meta-").map(|key| {
+                    (
+                        key.to_string(),
+                        String::from_utf8_lossy(v.as_bytes()).into_owned(),
+                    )
+                })
+            })
+            .collect();
 
         Ok(Self {
             last_modified,
             size,
-            md5,
+            md5: optional("Content-Md5").unwrap_or_default(),
+            e_tag: optional("ETag").unwrap_or_default(),
+            content_type: optional("Content-Type"),
+            content_encoding: optional("Content-Encoding"),
+            content_disposition: optional("Content-Disposition"),
+            cache_control: optional("Cache-Control"),
+            expires: optional("Expires"),
+            storage_class: optional("x-oss-storage-class").map(|s| StorageClass::from(s.as_str())),
+            object_type: optional("x-oss-object-type"),
+            version_id: optional("x-oss-version-id"),
+            crc64: number("x-oss-hash-crc64ecma"),
+            server_side_encryption: optional("x-oss-server-side-encryption"),
+            server_side_encryption_key_id: optional("x-oss-server-side-encryption-key-id"),
+            restore: optional("x-oss-restore"),
+            tagging_count: optional("x-oss-tagging-count").and_then(|v| v.parse().ok()),
+            next_append_position: number("x-oss-next-append-position"),
+            user_meta,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tls_options_build_both_clients() {
+        // a slow answer for `/slow`, to check the timeout
+        let url = crate::test_server::serve(|req| {
+            if req.target.ends_with("/slow") {
+                std::thread::sleep(Duration::from_millis(300));
+            }
+            crate::test_server::Response::ok(req.target)
+        });
+        let opts = Options {
+            timeout: Some(Duration::from_millis(100)),
+            proxy: Some(Proxy::http(&url).unwrap()),
+            danger_accept_invalid_certs: true,
+            min_tls_version: Some(tls::Version::TLS_1_2),
+            ..Default::default()
+        };
+        let oss = OSS::new_with_opts("ak", "sk", "oss-cn-hangzhou.aliyuncs.com", "bucket", opts);
+        let fast = "http://bucket.oss-cn-hangzhou.aliyuncs.com/fast";
+        let slow = "http://bucket.oss-cn-hangzhou.aliyuncs.com/slow";
+
+        // both clients go through the proxy and give up after the timeout
+        let blocking = oss.blocking_http_client();
+        assert_eq!(blocking.get(fast).send().unwrap().text().unwrap(), fast);
+        assert!(blocking.get(slow).send().unwrap_err().is_timeout());
+        // the stand-in answers one request at a time, let it finish the slow one
+        std::thread::sleep(Duration::from_millis(300));
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let resp = oss.http_client.get(fast).send().await.unwrap();
+            assert_eq!(resp.text().await.unwrap(), fast);
+            assert!(oss
+                .http_client
+                .get(slow)
+                .send()
+                .await
+                .unwrap_err()
+                .is_timeout());
+        });
+    }
+}
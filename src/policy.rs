@@ -0,0 +1,200 @@
+//! Bucket policies, the RAM policy documents attached to a bucket.
+
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+
+use crate::errors::Error;
+
+/// Operator, then condition key, then the values it is compared to, e.g.
+/// `{"IpAddress": {"acs:SourceIp": ["192.168.0.0/16"]}}`.
+pub type Conditions = BTreeMap<String, BTreeMap<String, Vec<String>>>;
+
+/// A policy document, the body of PutBucketPolicy and GetBucketPolicy.
+///
+/// # Examples
+///
+/// ```
+/// use oss_rust_sdk::policy::{PolicyDocument, Statement};
+///
+/// let policy = PolicyDocument::new(vec![
+///     Statement::public_read("my-bucket", "public/"),
+///     Statement::allow_write("my-bucket", "uploads/", "20214760404935xxxx")
+///         .condition("IpAddress", "acs:SourceIp", ["192.168.0.0/16"]),
+/// ]);
+///
+/// let json = policy.to_json().unwrap();
+/// assert_eq!(PolicyDocument::from_json(&json).unwrap(), policy);
+///
+/// // single values may be written without an array
+/// let parsed = PolicyDocument::from_json(
+///     r#"{"Version":"1","Statement":[{"Effect":"Deny","Action":"oss:DeleteObject",
+///     "Principal":"*","Resource":"acs:oss:*:*:my-bucket/*"}]}"#,
+/// )
+/// .unwrap();
+/// assert_eq!(parsed.statement[0].action, vec!["oss:DeleteObject"]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PolicyDocument {
+    pub version: String,
+    pub statement: Vec<Statement>,
+}
+
+impl PolicyDocument {
+    /// A document of version `1`.
+    pub fn new(statements: Vec<Statement>) -> Self {
+        PolicyDocument {
+            version: "1".to_string(),
+            statement: statements,
+        }
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        Ok(serde_json::to_string(self)?)
+    }
+
+    pub fn from_json(body: &str) -> Result<Self, Error> {
+        Ok(serde_json::from_str(body)?)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Effect {
+    Allow,
+    Deny,
+}
+
+/// A statement allowing or denying `action` on `resource` to `principal`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Statement {
+    pub effect: Effect,
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub action: Vec<String>,
+    /// RAM user ids, or `*` for anyone.
+    #[serde(
+        default,
+        deserialize_with = "one_or_many",
+        skip_serializing_if = "Vec::is_empty"
+    )]
+    pub principal: Vec<String>,
+    /// `acs:oss:*:*:{bucket}/{object}`, objects can contain `*`.
+    #[serde(default, deserialize_with = "one_or_many")]
+    pub resource: Vec<String>,
+    #[serde(
+        default,
+        deserialize_with = "conditions",
+        skip_serializing_if = "BTreeMap::is_empty"
+    )]
+    pub condition: Conditions,
+}
+
+impl Statement {
+    pub fn new(effect: Effect) -> Self {
+        Statement {
+            effect,
+            action: Vec::new(),
+            principal: Vec::new(),
+            resource: Vec::new(),
+            condition: BTreeMap::new(),
+        }
+    }
+
+    pub fn allow() -> Self {
+        Self::new(Effect::Allow)
+    }
+
+    pub fn deny() -> Self {
+        Self::new(Effect::Deny)
+    }
+
+    /// Anyone can read the objects under `prefix`.
+    pub fn public_read(bucket: &str, prefix: &str) -> Self {
+        Self::allow()
+            .principal("*")
+            .action("oss:GetObject")
+            .resource(object_resource(bucket, prefix))
+    }
+
+    /// The RAM user `uid` can upload objects under `prefix`, in one request or
+    /// with a multipart upload.
+    pub fn allow_write(bucket: &str, prefix: &str, uid: &str) -> Self {
+        Self::allow()
+            .principal(uid)
+            .action("oss:PutObject")
+            .action("oss:AbortMultipartUpload")
+            .action("oss:ListParts")
+            .resource(object_resource(bucket, prefix))
+    }
+
+    pub fn action<S: Into<String>>(mut self, action: S) -> Self {
+        self.action.push(action.into());
+        self
+    }
+
+    pub fn principal<S: Into<String>>(mut self, principal: S) -> Self {
+        self.principal.push(principal.into());
+        self
+    }
+
+    pub fn resource<S: Into<String>>(mut self, resource: S) -> Self {
+        self.resource.push(resource.into());
+        self
+    }
+
+    /// Only apply the statement when `key` matches `values` with `operator`,
+    /// e.g. `StringLike` or `IpAddress`.
+    pub fn condition<O, K, I, V>(mut self, operator: O, key: K, values: I) -> Self
+    where
+        O: Into<String>,
+        K: Into<String>,
+        I: IntoIterator<Item = V>,
+        V: Into<String>,
+    {
+        self.condition
+            .entry(operator.into())
+            .or_default()
+            .entry(key.into())
+            .or_default()
+            .extend(values.into_iter().map(Into::into));
+        self
+    }
+}
+
+/// Resource of the objects of `bucket` under `prefix`.
+pub fn object_resource(bucket: &str, prefix: &str) -> String {
+    format!("acs:oss:*:*:{}/{}*", bucket, prefix)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum OneOrMany {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl From<OneOrMany> for Vec<String> {
+    fn from(value: OneOrMany) -> Self {
+        match value {
+            OneOrMany::One(one) => vec![one],
+            OneOrMany::Many(many) => many,
+        }
+    }
+}
+
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    Ok(OneOrMany::deserialize(deserializer)?.into())
+}
+
+fn conditions<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Conditions, D::Error> {
+    let conditions = BTreeMap::<String, BTreeMap<String, OneOrMany>>::deserialize(deserializer)?;
+    Ok(conditions
+        .into_iter()
+        .map(|(operator, keys)| {
+            (
+                operator,
+                keys.into_iter().map(|(k, v)| (k, v.into())).collect(),
+            )
+        })
+        .collect())
+}
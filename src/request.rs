@@ -0,0 +1,453 @@
+use bytes::Bytes;
+use reqwest::header::{HeaderMap, CONTENT_LENGTH};
+use reqwest::Method;
+use std::collections::HashMap;
+use std::path::PathBuf;
+#[cfg(feature = "fs")]
+use tokio_util::io::ReaderStream;
+
+use super::credentials::Credentials;
+use super::errors::Error;
+use super::oss::{RequestType, OSS};
+use super::utils::to_headers;
+
+/// Body of a request, kept so that it can be sent again on retries.
+#[derive(Debug, Default)]
+pub(crate) enum RequestBody {
+    #[default]
+    Empty,
+    Bytes(Bytes),
+    /// Streamed from the file, which is opened again by every attempt.
+    File(PathBuf),
+    /// One-shot bodies, taken by the first attempt.
+    Stream(Option<reqwest::Body>),
+    Reader(Option<reqwest::blocking::Body>),
+}
+
+impl RequestBody {
+    /// Whether the body can be sent again by a retry.
+    fn is_replayable(&self) -> bool {
+        match self {
+            RequestBody::Empty | RequestBody::Bytes(_) | RequestBody::File(_) => true,
+            RequestBody::Stream(_) | RequestBody::Reader(_) => false,
+        }
+    }
+}
+
+/// An owned request, built and signed again for every attempt.
+#[derive(Debug)]
+pub(crate) struct Request {
+    req_type: RequestType,
+    object_name: String,
+    headers: HashMap<String, String>,
+    resources: HashMap<String, Option<String>>,
+    body: RequestBody,
+    service: bool,
+}
+
+impl Request {
+    pub fn new<S1, S2, H, R>(
+        req_type: RequestType,
+        object_name: S1,
+        headers: H,
+        resources: R,
+    ) -> Self
+    where
+        S1: AsRef<str>,
+        S2: AsRef<str>,
+        H: Into<Option<HashMap<S2, S2>>>,
+        R: Into<Option<HashMap<S2, Option<S2>>>>,
+    {
+        Request {
+            req_type,
+            object_name: object_name.as_ref().to_string(),
+            headers: headers
+                .into()
+                .map(|h| {
+                    h.iter()
+                        .map(|(k, v)| (k.as_ref().to_string(), v.as_ref().to_string()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            resources: resources
+                .into()
+                .map(|r| {
+                    r.iter()
+                        .map(|(k, v)| {
+                            (
+                                k.as_ref().to_string(),
+                                v.as_ref().map(|v| v.as_ref().to_string()),
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default(),
+            body: RequestBody::Empty,
+            service: false,
+        }
+    }
+
+    /// A request sent to the endpoint itself rather than to a bucket.
+    pub fn service<S, R>(req_type: RequestType, resources: R) -> Self
+    where
+        S: AsRef<str>,
+        R: Into<Option<HashMap<S, Option<S>>>>,
+    {
+        let mut request = Self::new(req_type, "", None, resources);
+        request.service = true;
+        request
+    }
+
+    pub fn header<K, V>(mut self, key: K, value: V) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.headers.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn resource<K, V>(mut self, key: K, value: Option<V>) -> Self
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        self.resources.insert(key.into(), value.map(Into::into));
+        self
+    }
+
+    pub fn headers(&self) -> &HashMap<String, String> {
+        &self.headers
+    }
+
+    pub fn resources(&self) -> &HashMap<String, Option<String>> {
+        &self.resources
+    }
+
+    pub fn body<B>(mut self, body: B) -> Self
+    where
+        B: Into<Bytes>,
+    {
+        self.body = RequestBody::Bytes(body.into());
+        self
+    }
+
+    /// Stream the body from a file of `len` bytes.
+    pub fn file_body<P>(mut self, path: P, len: u64) -> Self
+    where
+        P: Into<PathBuf>,
+    {
+        self.body = RequestBody::File(path.into());
+        self.header(CONTENT_LENGTH.as_str(), len.to_string())
+    }
+
+    /// Stream a body of `len` bytes, the request is not retried.
+    pub fn stream_body(mut self, body: reqwest::Body, len: u64) -> Self {
+        self.body = RequestBody::Stream(Some(body));
+        self.header(CONTENT_LENGTH.as_str(), len.to_string())
+    }
+
+    /// Stream a body of `len` bytes in blocking requests, the request is not retried.
+    pub fn reader_body(mut self, body: reqwest::blocking::Body, len: u64) -> Self {
+        self.body = RequestBody::Reader(Some(body));
+        self.header(CONTENT_LENGTH.as_str(), len.to_string())
+    }
+
+    fn method(&self) -> Method {
+        match self.req_type {
+            RequestType::Get => Method::GET,
+            RequestType::Put => Method::PUT,
+            RequestType::Post => Method::POST,
+            RequestType::Delete => Method::DELETE,
+            RequestType::Head => Method::HEAD,
+        }
+    }
+}
+
+impl<'a> OSS<'a> {
+    /// Url and signed headers of one attempt.
+    fn sign_request(
+        &self,
+        request: &Request,
+        credentials: &Credentials,
+    ) -> Result<(String, HeaderMap), Error> {
+        let params_str = self.get_encoded_params_str(&request.resources);
+        let (host, bucket) = if request.service {
+            (self.service_host(&params_str), "")
+        } else {
+            (
+                self.host(self.bucket(), &request.object_name, &params_str),
+                self.bucket(),
+            )
+        };
+        let mut headers = to_headers(request.headers.clone())?;
+        self.sign_headers(
+            credentials,
+            request.req_type.as_str(),
+            bucket,
+            &request.object_name,
+            Some(&request.resources),
+            &mut headers,
+        )?;
+        Ok((host, headers))
+    }
+
+    async fn send_once(&self, request: &mut Request) -> Result<reqwest::Response, Error> {
+        let credentials = self.credentials_async().await?;
+        let (host, headers) = self.sign_request(request, &credentials)?;
+        let mut builder = self
+            .http_client
+            .request(request.method(), &host)
+            .headers(headers);
+        match &mut request.body {
+            RequestBody::Empty => {}
+            RequestBody::Bytes(body) => builder = builder.body(body.clone()),
+            #[cfg(feature = "fs")]
+            RequestBody::File(path) => {
+                let file = tokio::fs::File::open(path).await?;
+                builder = builder.body(reqwest::Body::wrap_stream(ReaderStream::new(file)));
+            }
+            #[cfg(not(feature = "fs"))]
+            RequestBody::File(_) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Unsupported,
+                    "file bodies of the async api need the `fs` feature",
+                )
+                .into())
+            }
+            RequestBody::Stream(body) => {
+                if let Some(body) = body.take() {
+                    builder = builder.body(body);
+                }
+            }
+            RequestBody::Reader(_) => return Err(body_kind_error("blocking reader", "async")),
+        }
+
+        let resp = builder.send().await?;
+        if resp.status().is_success() {
+            Ok(resp)
+        } else {
+            Err(Error::from_response(resp).await)
+        }
+    }
+
+    fn send_once_blocking(
+        &self,
+        request: &mut Request,
+    ) -> Result<reqwest::blocking::Response, Error> {
+        let (host, headers) = self.sign_request(request, &self.credentials()?)?;
+        let mut builder = self
+            .blocking_http_client()
+            .request(request.method(), &host)
+            .headers(headers);
+        match &mut request.body {
+            RequestBody::Empty => {}
+            RequestBody::Bytes(body) => builder = builder.body(body.clone()),
+            RequestBody::File(path) => builder = builder.body(std::fs::File::open(path)?),
+            RequestBody::Reader(body) => {
+                if let Some(body) = body.take() {
+                    builder = builder.body(body);
+                }
+            }
+            RequestBody::Stream(_) => return Err(body_kind_error("async stream", "blocking")),
+        }
+
+        let resp = builder.send()?;
+        if resp.status().is_success() {
+            Ok(resp)
+        } else {
+            Err(Error::from_blocking_response(resp))
+        }
+    }
+
+    /// Send the request, retrying according to the retry policy of the client.
+    /// Responses with a non 2xx status are turned into `Error::Service`.
+    pub(crate) async fn send(&self, mut request: Request) -> Result<reqwest::Response, Error> {
+        let mut attempt = 1;
+        loop {
+            let err = match self.send_once(&mut request).await {
+                Ok(resp) => return Ok(resp),
+                Err(e) => e,
+            };
+            match self.retry_delay(&request, attempt, &err) {
+                Some(delay) => {
+                    warn!("attempt {} failed, retry in {:?}: {}", attempt, delay, err);
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                None => return Err(err),
+            }
+        }
+    }
+
+    pub(crate) fn send_blocking(
+        &self,
+        mut request: Request,
+    ) -> Result<reqwest::blocking::Response, Error> {
+        let mut attempt = 1;
+        loop {
+            let err = match self.send_once_blocking(&mut request) {
+                Ok(resp) => return Ok(resp),
+                Err(e) => e,
+            };
+            match self.retry_delay(&request, attempt, &err) {
+                Some(delay) => {
+                    warn!("attempt {} failed, retry in {:?}: {}", attempt, delay, err);
+                    std::thread::sleep(delay);
+                    attempt += 1;
+                }
+                None => return Err(err),
+            }
+        }
+    }
+
+    fn retry_delay(
+        &self,
+        request: &Request,
+        attempt: u32,
+        err: &Error,
+    ) -> Option<std::time::Duration> {
+        if !request.body.is_replayable() {
+            return None;
+        }
+        self.retry_policy()
+            .and_then(|policy| policy.next_delay(attempt, err))
+    }
+}
+
+/// A body the client can not send, rather than sending its `Content-Length`
+/// without it.
+fn body_kind_error(body: &str, api: &str) -> Error {
+    std::io::Error::new(
+        std::io::ErrorKind::InvalidInput,
+        format!("{} body can not be sent by the {} api", body, api),
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::credentials::{CallbackProvider, Credentials};
+    use crate::errors::ServiceErrorCode;
+    use crate::test_server::{self, Received, Response};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+
+    // rejected before anything is sent, the endpoint is never reached
+    fn oss() -> OSS<'static> {
+        OSS::new("ak", "sk", "http://127.0.0.1:1", "bucket")
+    }
+
+    fn assert_invalid_input(result: Result<(), Error>) {
+        match result {
+            Err(Error::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::InvalidInput),
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn async_api_rejects_reader_bodies() {
+        let body = reqwest::blocking::Body::sized(std::io::empty(), 3);
+        let request = Request::new(RequestType::Put, "a.txt", None::<HashMap<&str, &str>>, None)
+            .reader_body(body, 3);
+        assert_invalid_input(oss().send(request).await.map(|_| ()));
+    }
+
+    #[test]
+    fn blocking_api_rejects_stream_bodies() {
+        let request = Request::new(RequestType::Put, "a.txt", None::<HashMap<&str, &str>>, None)
+            .stream_body(reqwest::Body::from("abc"), 3);
+        assert_invalid_input(oss().send_blocking(request).map(|_| ()));
+    }
+
+    /// Stand-in answering with `statuses` in turn, then 200, and a client
+    /// whose credentials change on every request.
+    fn flaky_server(statuses: Vec<u16>) -> (OSS<'static>, Arc<Mutex<Vec<Received>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let received = requests.clone();
+        let mut statuses = statuses.into_iter();
+        let url = test_server::serve(move |request| {
+            received.lock().unwrap().push(request);
+            match statuses.next() {
+                Some(status) => Response::error(status, "ServiceUnavailable"),
+                None => Response::ok("done"),
+            }
+        });
+        let fetched = AtomicUsize::new(0);
+        let provider = CallbackProvider::new(move || {
+            let n = fetched.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(Credentials::new(format!("ak-{}", n), "sk".to_string()))
+        });
+        let oss = OSS::new_with_provider(
+            provider,
+            "oss-cn-hangzhou.aliyuncs.com",
+            "bucket",
+            test_server::options(&url),
+        );
+        (oss, requests)
+    }
+
+    fn assert_signed_by(requests: &Mutex<Vec<Received>>, access_key_ids: &[&str]) {
+        let requests = requests.lock().unwrap();
+        let signed_by: Vec<_> = requests
+            .iter()
+            .map(|r| {
+                let authorization = r.header("Authorization").unwrap();
+                authorization[4..].split(':').next().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(signed_by, access_key_ids);
+        assert!(requests.iter().all(|r| r.body_str() == "abc"));
+    }
+
+    fn put_request() -> Request {
+        Request::new(RequestType::Put, "a.txt", None::<HashMap<&str, &str>>, None).body("abc")
+    }
+
+    #[tokio::test]
+    async fn retries_and_signs_again() {
+        let (oss, requests) = flaky_server(vec![503]);
+        let resp = oss.send(put_request()).await.unwrap();
+        assert_eq!(resp.text().await.unwrap(), "done");
+        assert_signed_by(&requests, &["ak-1", "ak-2"]);
+    }
+
+    #[test]
+    fn blocking_retries_and_signs_again() {
+        let (oss, requests) = flaky_server(vec![503, 500]);
+        let resp = oss.send_blocking(put_request()).unwrap();
+        assert_eq!(resp.text().unwrap(), "done");
+        assert_signed_by(&requests, &["ak-1", "ak-2", "ak-3"]);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_max_attempts() {
+        let (oss, requests) = flaky_server(vec![503, 503, 503, 503]);
+        let err = oss.send(put_request()).await.unwrap_err();
+        assert_eq!(
+            err.service_error_code(),
+            Some(ServiceErrorCode::ServiceUnavailable)
+        );
+        assert_eq!(requests.lock().unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn stream_body_is_not_replayed() {
+        let (oss, requests) = flaky_server(vec![503]);
+        let request = Request::new(RequestType::Put, "a.txt", None::<HashMap<&str, &str>>, None)
+            .stream_body(reqwest::Body::from("abc"), 3);
+        assert!(oss.send(request).await.is_err());
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn reader_body_is_not_replayed() {
+        let (oss, requests) = flaky_server(vec![503]);
+        let body = reqwest::blocking::Body::sized(std::io::Cursor::new(b"abc".to_vec()), 3);
+        let request = Request::new(RequestType::Put, "a.txt", None::<HashMap<&str, &str>>, None)
+            .reader_body(body, 3);
+        assert!(oss.send_blocking(request).is_err());
+        assert_eq!(requests.lock().unwrap().len(), 1);
+    }
+}
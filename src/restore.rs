@@ -0,0 +1,411 @@
+//! Restore of Archive, ColdArchive and DeepColdArchive objects, which can
+//! only be read once restored.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::async_object::AsyncObjectAPI;
+use crate::errors::{Error, ObjectError, ServiceErrorCode};
+use crate::options::{string_enum, StorageClass};
+use crate::oss::{ObjectMeta, RequestType, OSS};
+use crate::request::Request;
+
+/// Priority of the restore of a ColdArchive or DeepColdArchive object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RestoreTier {
+    /// Within an hour for ColdArchive, 12 hours for DeepColdArchive.
+    Expedited,
+    /// Within 2 to 5 hours for ColdArchive, 48 hours for DeepColdArchive.
+    Standard,
+    /// Within 5 to 12 hours, ColdArchive only.
+    Bulk,
+}
+
+string_enum! {
+    RestoreTier {
+        Expedited => "Expedited",
+        Standard => "Standard",
+        Bulk => "Bulk",
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct JobParameters {
+    pub tier: RestoreTier,
+}
+
+/// Body of a RestoreObject request.
+///
+/// # Examples
+///
+/// ```
+/// use oss_rust_sdk::restore::{RestoreRequest, RestoreTier};
+///
+/// let request = RestoreRequest::new(2).tier(RestoreTier::Bulk);
+/// assert_eq!(
+///     request.to_xml().unwrap(),
+///     "<RestoreRequest><Days>2</Days><JobParameters><Tier>Bulk</Tier></JobParameters></RestoreRequest>"
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct RestoreRequest {
+    /// Number of days the object stays readable.
+    pub days: u32,
+    /// Only used by ColdArchive and DeepColdArchive objects.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub job_parameters: Option<JobParameters>,
+}
+
+impl RestoreRequest {
+    pub fn new(days: u32) -> Self {
+        RestoreRequest {
+            days,
+            job_parameters: None,
+        }
+    }
+
+    pub fn tier(mut self, tier: RestoreTier) -> Self {
+        self.job_parameters = Some(JobParameters { tier });
+        self
+    }
+
+    pub fn to_xml(&self) -> Result<String, Error> {
+        Ok(quick_xml::se::to_string_with_root("RestoreRequest", self)?)
+    }
+}
+
+impl Default for RestoreRequest {
+    fn default() -> Self {
+        RestoreRequest::new(1)
+    }
+}
+
+/// Restore status of an object, parsed from `x-oss-restore`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RestoreStatus {
+    /// The object is being restored.
+    Ongoing,
+    /// The object is readable until `expiry_date`.
+    Restored { expiry_date: Option<SystemTime> },
+}
+
+impl RestoreStatus {
+    /// Parse `ongoing-request="true"` or
+    /// `ongoing-request="false", expiry-date="Sun, 16 Apr 2017 08:12:33 GMT"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use oss_rust_sdk::restore::RestoreStatus;
+    ///
+    /// let ongoing = RestoreStatus::parse(r#"ongoing-request="true""#);
+    /// assert_eq!(ongoing, Some(RestoreStatus::Ongoing));
+    ///
+    /// let restored = RestoreStatus::parse(
+    ///     r#"ongoing-request="false", expiry-date="Sun, 16 Apr 2017 08:12:33 GMT""#,
+    /// );
+    /// assert!(restored.unwrap().is_restored());
+    /// ```
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut ongoing = None;
+        let mut expiry_date = None;
+        for field in value.split("\",") {
+            let (key, value) = match field.split_once('=') {
+                Some(kv) => kv,
+                None => continue,
+            };
+            let value = value.trim().trim_matches('"');
+            match key.trim() {
+                "ongoing-request" => ongoing = Some(value == "true"),
+                "expiry-date" => expiry_date = httpdate::parse_http_date(value).ok(),
+                _ => (),
+            }
+        }
+        match ongoing? {
+            true => Some(RestoreStatus::Ongoing),
+            false => Some(RestoreStatus::Restored { expiry_date }),
+        }
+    }
+
+    pub fn is_restored(&self) -> bool {
+        matches!(self, RestoreStatus::Restored { .. })
+    }
+}
+
+impl ObjectMeta {
+    /// Restore status of an archived object, `None` when no restore was
+    /// requested.
+    pub fn restore_status(&self) -> Option<RestoreStatus> {
+        self.restore.as_deref().and_then(RestoreStatus::parse)
+    }
+
+    /// Whether the object can be read, i.e. it is not archived or is restored.
+    pub fn is_readable(&self) -> bool {
+        match self.storage_class {
+            Some(StorageClass::Archive)
+            | Some(StorageClass::ColdArchive)
+            | Some(StorageClass::DeepColdArchive) => self
+                .restore_status()
+                .map(|s| s.is_restored())
+                .unwrap_or(false),
+            _ => true,
+        }
+    }
+}
+
+pub(crate) fn restore_object_request<S>(
+    object_name: S,
+    request: &RestoreRequest,
+) -> Result<Request, Error>
+where
+    S: AsRef<str>,
+{
+    Ok(Request::new(
+        RequestType::Post,
+        object_name,
+        None::<HashMap<String, String>>,
+        None,
+    )
+    .resource("restore", None::<String>)
+    .body(request.to_xml()?))
+}
+
+/// Request the restore of `object_name`, unless it is already in progress,
+/// then wait until it can be read.
+pub async fn restore_and_wait<S>(
+    oss: &OSS<'_>,
+    object_name: S,
+    request: &RestoreRequest,
+    poll_interval: Duration,
+    timeout: Option<Duration>,
+) -> Result<ObjectMeta, Error>
+where
+    S: AsRef<str> + Send,
+{
+    let object_name = object_name.as_ref();
+    match oss.restore_object(object_name, request).await {
+        Ok(()) => (),
+        Err(e)
+            if matches!(
+                e.service_error_code(),
+                Some(ServiceErrorCode::RestoreAlreadyInProgress)
+            ) => {}
+        Err(e) => return Err(e),
+    }
+    wait_until_restored(oss, object_name, poll_interval, timeout).await
+}
+
+/// Head `object_name` every `poll_interval` until it can be read, failing
+/// after `timeout` or when no restore was requested.
+pub async fn wait_until_restored<S>(
+    oss: &OSS<'_>,
+    object_name: S,
+    poll_interval: Duration,
+    timeout: Option<Duration>,
+) -> Result<ObjectMeta, Error>
+where
+    S: AsRef<str> + Send,
+{
+    let object_name = object_name.as_ref();
+    let start = Instant::now();
+    loop {
+        let meta = oss.head_object(object_name).await?;
+        if meta.is_readable() {
+            return Ok(meta);
+        }
+        if meta.restore_status().is_none() {
+            return Err(Error::Object(ObjectError::GetError {
+                msg: format!("restore of {} was not requested", object_name),
+            }));
+        }
+        if let Some(timeout) = timeout {
+            if start.elapsed() + poll_interval > timeout {
+                return Err(Error::Object(ObjectError::GetError {
+                    msg: format!("{} still not restored after {:?}", object_name, timeout),
+                }));
+            }
+        }
+        debug!("{} is being restored", object_name);
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_server::{self, Received, Response};
+    use reqwest::header::HeaderMap;
+    use std::sync::{Arc, Mutex};
+
+    const ONGOING: &str = r#"ongoing-request="true""#;
+    const RESTORED: &str =
+        r#"ongoing-request="false", expiry-date="Sun, 16 Apr 2017 08:12:33 GMT""#;
+
+    /// Answer the restore with `restore`, and the HEADs of an Archive object
+    /// with the `x-oss-restore` of `heads` in turn, the last one repeated.
+    fn archive_server(
+        restore: Response,
+        heads: Vec<Option<&'static str>>,
+    ) -> (String, Arc<Mutex<Vec<Received>>>) {
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let log = requests.clone();
+        let mut restore = Some(restore);
+        let mut served = 0;
+        let url = test_server::serve(move |req| {
+            let method = req.method.clone();
+            log.lock().unwrap().push(req);
+            if method != "HEAD" {
+                return restore.take().unwrap_or_else(|| Response::status(500));
+            }
+            let status = heads[served.min(heads.len() - 1)];
+            served += 1;
+            let response = Response::ok("")
+                .header("Content-Length", 5)
+                .header("Last-Modified", "Sun, 17 Dec 2023 02:54:37 GMT")
+                .header("x-oss-storage-class", "Archive");
+            match status {
+                Some(status) => response.header("x-oss-restore", status),
+                None => response,
+            }
+        });
+        (url, requests)
+    }
+
+    fn meta(storage_class: &str, restore: Option<&str>) -> ObjectMeta {
+        let mut headers = HeaderMap::new();
+        headers.insert("Content-Length", "5".parse().unwrap());
+        headers.insert(
+            "Last-Modified",
+            "Sun, 17 Dec 2023 02:54:37 GMT".parse().unwrap(),
+        );
+        headers.insert("x-oss-storage-class", storage_class.parse().unwrap());
+        if let Some(restore) = restore {
+            headers.insert("x-oss-restore", restore.parse().unwrap());
+        }
+        ObjectMeta::from_header_map(&headers).unwrap()
+    }
+
+    fn head_count(requests: &Mutex<Vec<Received>>) -> usize {
+        requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|req| req.method == "HEAD")
+            .count()
+    }
+
+    #[test]
+    fn readable_unless_archived_and_not_restored() {
+        assert!(meta("Standard", None).is_readable());
+        assert!(meta("IA", None).is_readable());
+        assert!(!meta("Archive", None).is_readable());
+        assert!(!meta("ColdArchive", Some(ONGOING)).is_readable());
+        assert!(meta("DeepColdArchive", Some(RESTORED)).is_readable());
+        assert_eq!(meta("Archive", None).restore_status(), None);
+        assert_eq!(
+            meta("Archive", Some(ONGOING)).restore_status(),
+            Some(RestoreStatus::Ongoing)
+        );
+    }
+
+    #[tokio::test]
+    async fn unrequested_restore_is_an_error() {
+        let (url, requests) = archive_server(Response::status(202), vec![None]);
+        let oss = test_server::client(&url);
+
+        let err = wait_until_restored(&oss, "a.txt", Duration::from_millis(1), None)
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, Error::Object(ObjectError::GetError { msg }) if msg.contains("not requested")),
+            "{:?}",
+            err
+        );
+        assert_eq!(head_count(&requests), 1);
+    }
+
+    #[tokio::test]
+    async fn waits_while_the_restore_is_ongoing() {
+        let (url, requests) =
+            archive_server(Response::status(202), vec![Some(ONGOING), Some(RESTORED)]);
+        let oss = test_server::client(&url);
+
+        let meta = wait_until_restored(&oss, "a.txt", Duration::from_millis(1), None)
+            .await
+            .unwrap();
+        assert!(meta.restore_status().unwrap().is_restored());
+        assert_eq!(head_count(&requests), 2);
+    }
+
+    #[tokio::test]
+    async fn gives_up_after_the_timeout() {
+        let (url, requests) = archive_server(Response::status(202), vec![Some(ONGOING)]);
+        let oss = test_server::client(&url);
+
+        let err = wait_until_restored(
+            &oss,
+            "a.txt",
+            Duration::from_millis(20),
+            Some(Duration::from_millis(50)),
+        )
+        .await
+        .unwrap_err();
+        assert!(
+            matches!(&err, Error::Object(ObjectError::GetError { msg }) if msg.contains("still not restored")),
+            "{:?}",
+            err
+        );
+        let heads = head_count(&requests);
+        assert!((1..=3).contains(&heads), "{} HEADs", heads);
+    }
+
+    #[tokio::test]
+    async fn restore_already_in_progress_is_waited_for() {
+        let (url, requests) = archive_server(
+            Response::error(409, "RestoreAlreadyInProgress"),
+            vec![Some(ONGOING), Some(RESTORED)],
+        );
+        let oss = test_server::client(&url);
+
+        let meta = restore_and_wait(
+            &oss,
+            "a.txt",
+            &RestoreRequest::new(2),
+            Duration::from_millis(1),
+            None,
+        )
+        .await
+        .unwrap();
+        assert!(meta.is_readable());
+        let requests = requests.lock().unwrap();
+        assert_eq!(requests[0].method, "POST");
+        assert_eq!(requests[0].query("restore"), Some(""));
+        assert!(requests[0].body_str().contains("<Days>2</Days>"));
+        assert_eq!(requests.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn other_restore_errors_are_returned() {
+        let (url, requests) =
+            archive_server(Response::error(403, "AccessDenied"), vec![Some(RESTORED)]);
+        let oss = test_server::client(&url);
+
+        let err = restore_and_wait(
+            &oss,
+            "a.txt",
+            &RestoreRequest::default(),
+            Duration::from_millis(1),
+            None,
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(
+            err.service_error_code(),
+            Some(ServiceErrorCode::AccessDenied)
+        ));
+        assert_eq!(head_count(&requests), 0);
+    }
+}
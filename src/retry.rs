@@ -0,0 +1,201 @@
+use rand::Rng;
+use reqwest::StatusCode;
+use std::fmt;
+use std::io::ErrorKind;
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::errors::{Error, ServiceErrorCode};
+
+/// Decides whether a failed attempt should be retried.
+pub type RetryClassifier = Arc<dyn Fn(&Error) -> bool + Send + Sync>;
+
+/// How failed requests are retried.
+///
+/// Every attempt is signed again, and requests whose body can not be replayed
+/// are never retried.
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one.
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled on every following one.
+    pub backoff_base: Duration,
+    /// Upper bound of the delay between two attempts.
+    pub backoff_cap: Duration,
+    /// Sleep a random duration between zero and the backoff ("full jitter").
+    pub jitter: bool,
+    /// Overrides [`RetryPolicy::is_retryable`] when set.
+    pub retryable: Option<RetryClassifier>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            backoff_base: Duration::from_millis(200),
+            backoff_cap: Duration::from_secs(20),
+            jitter: true,
+            retryable: None,
+        }
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("backoff_base", &self.backoff_base)
+            .field("backoff_cap", &self.backoff_cap)
+            .field("jitter", &self.jitter)
+            .field("retryable", &self.retryable.as_ref().map(|_| "custom"))
+            .finish()
+    }
+}
+
+impl RetryPolicy {
+    /// Default classifier: throttling and server side failures, clock skew,
+    /// expired tokens, and connection or timeout errors.
+    pub fn is_retryable(error: &Error) -> bool {
+        match error {
+            Error::Service(e) => {
+                e.status == StatusCode::TOO_MANY_REQUESTS
+                    || e.status.is_server_error()
+                    || matches!(
+                        e.error_code(),
+                        ServiceErrorCode::RequestTimeTooSkewed
+                            | ServiceErrorCode::RequestTimeout
+                            | ServiceErrorCode::SecurityTokenExpired
+                            | ServiceErrorCode::InternalError
+                            | ServiceErrorCode::ServiceUnavailable
+                    )
+            }
+            Error::Reqwest(e) => e.is_timeout() || e.is_connect() || e.is_request(),
+            Error::Io(e) => matches!(
+                e.kind(),
+                ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::BrokenPipe
+                    | ErrorKind::TimedOut
+                    | ErrorKind::UnexpectedEof
+            ),
+            _ => false,
+        }
+    }
+
+    /// Delay before the next attempt, `None` when the request should fail.
+    /// `attempt` is the number of the attempt which just failed, from 1; 0 is
+    /// taken as 1.
+    pub fn next_delay(&self, attempt: u32, error: &Error) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+        let retryable = match &self.retryable {
+            Some(retryable) => retryable(error),
+            None => Self::is_retryable(error),
+        };
+        if !retryable {
+            return None;
+        }
+
+        let backoff = self
+            .backoff_base
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+            .min(self.backoff_cap);
+        if self.jitter {
+            Some(backoff.mul_f64(rand::thread_rng().gen_range(0.0..=1.0)))
+        } else {
+            Some(backoff)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::ServiceError;
+    use reqwest::header::HeaderMap;
+
+    fn service_error(status: u16, code: &str) -> Error {
+        let body = format!("<Error><Code>{}</Code></Error>", code);
+        Error::Service(Box::new(ServiceError::from_response(
+            StatusCode::from_u16(status).unwrap(),
+            &HeaderMap::new(),
+            body.as_bytes(),
+        )))
+    }
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 5,
+            backoff_base: Duration::from_millis(100),
+            backoff_cap: Duration::from_millis(300),
+            jitter: false,
+            retryable: None,
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let err = service_error(503, "ServiceUnavailable");
+        let delays: Vec<_> = (0..=5).map(|a| policy().next_delay(a, &err)).collect();
+        let ms = |ms| Some(Duration::from_millis(ms));
+        assert_eq!(delays, [ms(100), ms(100), ms(200), ms(300), ms(300), None]);
+    }
+
+    #[test]
+    fn jitter_stays_under_the_backoff() {
+        let policy = RetryPolicy {
+            jitter: true,
+            ..policy()
+        };
+        let err = service_error(500, "InternalError");
+        for _ in 0..100 {
+            assert!(policy.next_delay(2, &err).unwrap() <= Duration::from_millis(200));
+        }
+    }
+
+    #[test]
+    fn retries_transient_errors_only() {
+        for (status, code) in [
+            (429, "Throttling"),
+            (503, "ServiceUnavailable"),
+            (403, "RequestTimeTooSkewed"),
+            (403, "SecurityTokenExpired"),
+            (400, "RequestTimeout"),
+        ] {
+            let err = service_error(status, code);
+            assert!(policy().next_delay(1, &err).is_some(), "{}", code);
+        }
+        for (status, code) in [
+            (403, "AccessDenied"),
+            (403, "SignatureDoesNotMatch"),
+            (404, "NoSuchKey"),
+            (409, "PositionNotEqualToLength"),
+            (412, "PreconditionFailed"),
+        ] {
+            let err = service_error(status, code);
+            assert_eq!(policy().next_delay(1, &err), None, "{}", code);
+        }
+        let err = Error::Object(crate::errors::ObjectError::GetError {
+            msg: "invalid response".to_string(),
+        });
+        assert_eq!(policy().next_delay(1, &err), None);
+    }
+
+    #[test]
+    fn custom_classifier_overrides_the_default() {
+        let policy = RetryPolicy {
+            retryable: Some(Arc::new(|e: &Error| {
+                e.service_error_code() == Some(ServiceErrorCode::NoSuchKey)
+            })),
+            ..policy()
+        };
+        assert!(policy
+            .next_delay(1, &service_error(404, "NoSuchKey"))
+            .is_some());
+        assert_eq!(
+            policy.next_delay(1, &service_error(503, "ServiceUnavailable")),
+            None
+        );
+    }
+}
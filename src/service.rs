@@ -1,12 +1,11 @@
-use reqwest::header::{HeaderMap, DATE};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 use crate::prelude::Owner;
 
-use super::auth::*;
 use super::errors::Error;
-use super::oss::OSS;
+use super::oss::{RequestType, OSS};
+use super::request::Request;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -156,31 +155,7 @@ impl<'a> ServiceAPI for OSS<'a> {
         S: AsRef<str>,
         R: Into<Option<HashMap<S, Option<S>>>>,
     {
-        let resources_str = if let Some(r) = resources.into() {
-            self.get_resources_str(&r)
-        } else {
-            String::new()
-        };
-        let host = self.endpoint();
-        let date = self.date();
-
-        let mut headers = HeaderMap::new();
-        headers.insert(DATE, date.parse()?);
-        let authorization = self.oss_sign(
-            "GET",
-            self.key_id(),
-            self.key_secret(),
-            "",
-            "",
-            &resources_str,
-            &headers,
-        );
-        headers.insert("Authorization", authorization.parse()?);
-
-        let resp = reqwest::blocking::Client::new()
-            .get(host)
-            .headers(headers)
-            .send()?;
+        let resp = self.send_blocking(Request::service(RequestType::Get, resources))?;
 
         let body = resp.text()?;
         let list_buckets = quick_xml::de::from_str::<ListBuckets>(&body)?;
@@ -0,0 +1,121 @@
+//! Tags of objects, used by lifecycle rules and for billing.
+
+use serde::{Deserialize, Serialize};
+
+use crate::errors::Error;
+use crate::object::object_request;
+use crate::options::{encode_tags, ObjectOptions};
+use crate::oss::RequestType;
+use crate::request::Request;
+
+/// Tags of an object, the body of PutObjectTagging and GetObjectTagging.
+///
+/// # Examples
+///
+/// ```
+/// use oss_rust_sdk::tagging::Tagging;
+///
+/// let tagging = Tagging::new([("project", "demo"), ("cost center", "r&d")]);
+/// let xml = tagging.to_xml().unwrap();
+/// assert_eq!(Tagging::from_xml(&xml).unwrap(), tagging);
+/// assert_eq!(tagging.get("project"), Some("demo"));
+/// assert_eq!(tagging.to_header(), "project=demo&cost%20center=r%26d");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Tagging {
+    #[serde(default)]
+    pub tag_set: TagSet,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct TagSet {
+    #[serde(default)]
+    pub tag: Vec<Tag>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Tag {
+    pub key: String,
+    #[serde(default)]
+    pub value: String,
+}
+
+impl Tagging {
+    pub fn new<I, K, V>(tags: I) -> Self
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: Into<String>,
+        V: Into<String>,
+    {
+        Tagging {
+            tag_set: TagSet {
+                tag: tags
+                    .into_iter()
+                    .map(|(key, value)| Tag {
+                        key: key.into(),
+                        value: value.into(),
+                    })
+                    .collect(),
+            },
+        }
+    }
+
+    pub fn tags(&self) -> &[Tag] {
+        &self.tag_set.tag
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.tag_set
+            .tag
+            .iter()
+            .find(|t| t.key == key)
+            .map(|t| t.value.as_str())
+    }
+
+    /// Add a tag, replacing the value of an existing key.
+    pub fn insert<K, V>(&mut self, key: K, value: V)
+    where
+        K: Into<String>,
+        V: Into<String>,
+    {
+        let (key, value) = (key.into(), value.into());
+        match self.tag_set.tag.iter_mut().find(|t| t.key == key) {
+            Some(tag) => tag.value = value,
+            None => self.tag_set.tag.push(Tag { key, value }),
+        }
+    }
+
+    /// The tags as the url encoded query string of `x-oss-tagging`.
+    pub fn to_header(&self) -> String {
+        let tags: Vec<(String, String)> = self
+            .tag_set
+            .tag
+            .iter()
+            .map(|t| (t.key.clone(), t.value.clone()))
+            .collect();
+        encode_tags(&tags)
+    }
+
+    pub fn to_xml(&self) -> Result<String, Error> {
+        Ok(quick_xml::se::to_string_with_root("Tagging", self)?)
+    }
+
+    pub fn from_xml(body: &str) -> Result<Self, Error> {
+        Ok(quick_xml::de::from_str(body)?)
+    }
+}
+
+/// A `?tagging` request of `object_name`, or of the version of `options`.
+pub(crate) fn tagging_request<S>(
+    req_type: RequestType,
+    object_name: S,
+    options: ObjectOptions,
+) -> Request
+where
+    S: AsRef<str>,
+{
+    object_request(req_type, object_name, options).resource("tagging", None::<String>)
+}
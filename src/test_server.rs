@@ -0,0 +1,193 @@
+//! Local stand-in of the servers the client talks to, for the unit tests.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use crate::oss::{Options, OSS};
+use crate::retry::RetryPolicy;
+
+/// A request received by the stand-in. `target` is the target of the request
+/// line, the absolute url when the client is configured with the stand-in as
+/// its proxy.
+#[derive(Clone, Debug)]
+pub(crate) struct Received {
+    pub method: String,
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Received {
+    pub fn body_str(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Value of the header `name`, compared case insensitively.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Value of the query parameter `key` as sent, empty for a parameter
+    /// without value.
+    pub fn query(&self, key: &str) -> Option<&str> {
+        let (_, query) = self.target.split_once('?')?;
+        query
+            .split('&')
+            .find_map(|param| match param.split_once('=') {
+                Some((k, v)) if k == key => Some(v),
+                None if param == key => Some(""),
+                _ => None,
+            })
+    }
+}
+
+pub(crate) struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    pub fn ok<B: Into<Vec<u8>>>(body: B) -> Self {
+        Response {
+            status: 200,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    /// An OSS error response with the error `code`.
+    pub fn error(status: u16, code: &str) -> Self {
+        Response {
+            status,
+            headers: Vec::new(),
+            body: format!(
+                "<Error><Code>{}</Code><Message>stand-in error</Message>\
+                 <RequestId>5C3D9175B6FC201293AD****</RequestId></Error>",
+                code
+            )
+            .into_bytes(),
+        }
+    }
+
+    /// Add a header, a `Content-Length` replaces the length of the body, e.g.
+    /// to answer a HEAD request.
+    pub fn header<K: Into<String>, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.headers.push((key.into(), value.to_string()));
+        self
+    }
+}
+
+/// Answer every request with `handler`, one connection per request, and
+/// return the base url of the stand-in.
+pub(crate) fn serve<F>(mut handler: F) -> String
+where
+    F: FnMut(Received) -> Response + Send + 'static,
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            // a client giving up on a request must not stop the stand-in
+            if let Err(e) = stream.and_then(|stream| answer(stream, &mut handler)) {
+                eprintln!("stand-in: {}", e);
+            }
+        }
+    });
+    url
+}
+
+fn answer<F>(mut stream: TcpStream, handler: &mut F) -> io::Result<()>
+where
+    F: FnMut(Received) -> Response,
+{
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    let mut headers = Vec::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line)?;
+        if line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_string(), value.trim().to_string()));
+        }
+    }
+    let content_length = headers
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, v)| v.parse().ok())
+        .unwrap_or(0);
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let mut parts = request_line.split(' ');
+    let received = Received {
+        method: parts.next().unwrap_or_default().to_string(),
+        target: parts.next().unwrap_or_default().to_string(),
+        headers,
+        body,
+    };
+    let head_request = received.method == "HEAD";
+    let response = handler(received);
+    let mut head = format!(
+        "HTTP/1.1 {} Stand-in\r\nConnection: close\r\n",
+        response.status
+    );
+    if !response
+        .headers
+        .iter()
+        .any(|(k, _)| k.eq_ignore_ascii_case("content-length"))
+    {
+        head += &format!("Content-Length: {}\r\n", response.body.len());
+    }
+    for (k, v) in &response.headers {
+        head += &format!("{}: {}\r\n", k, v);
+    }
+    head += "\r\n";
+    stream.write_all(head.as_bytes())?;
+    if !head_request {
+        stream.write_all(&response.body)?;
+    }
+    Ok(())
+}
+
+/// Options sending every request to the stand-in at `url`, as a proxy, and
+/// retrying twice without waiting.
+pub(crate) fn options(url: &str) -> Options {
+    Options {
+        proxy: Some(reqwest::Proxy::http(url).unwrap()),
+        retry_policy: Some(RetryPolicy {
+            backoff_base: Duration::from_millis(1),
+            jitter: false,
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// Client of `bucket` with the [`options`] of the stand-in at `url`.
+pub(crate) fn client(url: &str) -> OSS<'static> {
+    OSS::new_with_opts(
+        "ak",
+        "sk",
+        "oss-cn-hangzhou.aliyuncs.com",
+        "bucket",
+        options(url),
+    )
+}
@@ -1,21 +1,7 @@
 use super::errors::Error;
+use md5::{Digest, Md5};
 use reqwest::header::{HeaderMap, HeaderName};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{BufReader, Read};
-
-#[inline]
-pub fn load_file<S>(p: S) -> Result<Vec<u8>, Error>
-where
-    S: AsRef<str>,
-{
-    let p = p.as_ref();
-    let f = File::open(p)?;
-    let mut f = BufReader::new(f);
-    let mut s = Vec::new();
-    f.read_to_end(&mut s)?;
-    Ok(s)
-}
 
 pub fn to_headers<S>(hashmap: HashMap<S, S>) -> Result<HeaderMap, Error>
 where
@@ -29,3 +15,8 @@ where
     }
     Ok(headers)
 }
+
+/// Base64 encoded MD5 of a body, sent in `Content-MD5`.
+pub fn content_md5(body: &[u8]) -> String {
+    base64::encode(Md5::digest(body))
+}
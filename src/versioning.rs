@@ -0,0 +1,226 @@
+//! Versioning of a bucket, keeping the previous versions of the objects
+//! that are overwritten or deleted.
+//!
+//! A previous version is restored by copying it over the object, see
+//! [`CopyObjectOptions::source_version_id`](crate::options::CopyObjectOptions::source_version_id),
+//! and a deleted object by deleting its delete marker.
+
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+
+use crate::errors::Error;
+use crate::object::{url_decode, CommonPrefix, Owner};
+use crate::options::{string_enum, StorageClass};
+
+/// Versioning state of a bucket, it can not be disabled once enabled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum VersioningStatus {
+    Enabled,
+    Suspended,
+}
+
+string_enum! {
+    VersioningStatus {
+        Enabled => "Enabled",
+        Suspended => "Suspended",
+    }
+}
+
+/// Body of PutBucketVersioning and GetBucketVersioning, without status when
+/// versioning was never enabled.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct VersioningConfiguration {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<VersioningStatus>,
+}
+
+impl VersioningConfiguration {
+    pub fn new(status: VersioningStatus) -> Self {
+        VersioningConfiguration {
+            status: Some(status),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.status == Some(VersioningStatus::Enabled)
+    }
+
+    pub fn to_xml(&self) -> Result<String, Error> {
+        Ok(quick_xml::se::to_string_with_root(
+            "VersioningConfiguration",
+            self,
+        )?)
+    }
+
+    pub fn from_xml(body: &str) -> Result<Self, Error> {
+        Ok(quick_xml::de::from_str(body)?)
+    }
+}
+
+/// A page of ListObjectVersions. Pass `next_key_marker` and
+/// `next_version_id_marker` as `key-marker` and `version-id-marker` to list
+/// the next one.
+///
+/// # Examples
+///
+/// ```
+/// use oss_rust_sdk::versioning::ListObjectVersions;
+///
+/// let page = ListObjectVersions::from_xml(
+///     r#"<ListVersionsResult>
+///       <Name>bucket</Name>
+///       <Prefix></Prefix>
+///       <KeyMarker></KeyMarker>
+///       <VersionIdMarker></VersionIdMarker>
+///       <MaxKeys>100</MaxKeys>
+///       <IsTruncated>true</IsTruncated>
+///       <NextKeyMarker>b.txt</NextKeyMarker>
+///       <NextVersionIdMarker>CAEQ3</NextVersionIdMarker>
+///       <DeleteMarker>
+///         <Key>a.txt</Key>
+///         <VersionId>CAEQ1</VersionId>
+///         <IsLatest>true</IsLatest>
+///         <LastModified>2023-04-09T07:27:28.000Z</LastModified>
+///       </DeleteMarker>
+///       <Version>
+///         <Key>a.txt</Key>
+///         <VersionId>CAEQ0</VersionId>
+///         <IsLatest>false</IsLatest>
+///         <LastModified>2023-04-08T07:27:28.000Z</LastModified>
+///         <ETag>"5B3C1A2E053D763E1B002CC607C5A0FE"</ETag>
+///         <Type>Normal</Type>
+///         <Size>5</Size>
+///         <StorageClass>Standard</StorageClass>
+///       </Version>
+///       <DeleteMarker>
+///         <Key>b.txt</Key>
+///         <VersionId>CAEQ3</VersionId>
+///         <IsLatest>true</IsLatest>
+///         <LastModified>2023-04-09T07:27:28.000Z</LastModified>
+///       </DeleteMarker>
+///     </ListVersionsResult>"#,
+/// )
+/// .unwrap();
+///
+/// assert_eq!(page.version.len(), 1);
+/// assert_eq!(page.delete_marker.len(), 2);
+/// assert_eq!(page.version[0].version_id, "CAEQ0");
+/// assert_eq!(page.next_version_id_marker, "CAEQ3");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ListObjectVersions {
+    pub name: String,
+    #[serde(default)]
+    pub prefix: String,
+    #[serde(default)]
+    pub key_marker: String,
+    #[serde(default)]
+    pub version_id_marker: String,
+    #[serde(default)]
+    pub next_key_marker: String,
+    #[serde(default)]
+    pub next_version_id_marker: String,
+    #[serde(default)]
+    pub delimiter: String,
+    #[serde(default)]
+    pub encoding_type: String,
+    #[serde(default)]
+    pub max_keys: u32,
+    #[serde(default)]
+    pub is_truncated: bool,
+    #[serde(default)]
+    pub version: Vec<ObjectVersion>,
+    #[serde(default)]
+    pub delete_marker: Vec<DeleteMarker>,
+    #[serde(default)]
+    pub common_prefixes: Vec<CommonPrefix>,
+}
+
+impl ListObjectVersions {
+    /// Parse a response, decoding the keys and prefixes when the listing was
+    /// requested with `encoding-type=url`.
+    pub fn from_xml(body: &str) -> Result<Self, Error> {
+        let mut list = quick_xml::de::from_str::<ListObjectVersions>(body)?;
+        if list.encoding_type == "url" {
+            for s in [
+                &mut list.prefix,
+                &mut list.key_marker,
+                &mut list.next_key_marker,
+                &mut list.delimiter,
+            ] {
+                url_decode(s);
+            }
+            for version in list.version.iter_mut() {
+                url_decode(&mut version.key);
+            }
+            for marker in list.delete_marker.iter_mut() {
+                url_decode(&mut marker.key);
+            }
+            for prefix in list.common_prefixes.iter_mut() {
+                url_decode(&mut prefix.prefix);
+            }
+        }
+        Ok(list)
+    }
+}
+
+/// A version of an object.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ObjectVersion {
+    pub key: String,
+    /// `null` for the version written before versioning was enabled.
+    pub version_id: String,
+    /// Whether this is the current version.
+    pub is_latest: bool,
+    pub last_modified: String,
+    pub e_tag: String,
+    pub r#type: String,
+    pub size: u64,
+    pub storage_class: StorageClass,
+    #[serde(default)]
+    pub owner: Owner,
+}
+
+/// Version left by the deletion of an object on a versioned bucket, the
+/// object is restored by deleting it.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeleteMarker {
+    pub key: String,
+    pub version_id: String,
+    pub is_latest: bool,
+    pub last_modified: String,
+    #[serde(default)]
+    pub owner: Owner,
+}
+
+/// Result of a DeleteObject request.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DeleteObjectResult {
+    /// Version deleted, or delete marker created.
+    pub version_id: Option<String>,
+    pub delete_marker: bool,
+}
+
+impl DeleteObjectResult {
+    pub(crate) fn from_header_map(headers: &HeaderMap) -> Self {
+        DeleteObjectResult {
+            version_id: version_id(headers),
+            delete_marker: headers
+                .get("x-oss-delete-marker")
+                .and_then(|v| v.to_str().ok())
+                == Some("true"),
+        }
+    }
+}
+
+/// `x-oss-version-id` of a response, absent when versioning is not enabled.
+pub(crate) fn version_id(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("x-oss-version-id")
+        .and_then(|v| v.to_str().ok())
+        .map(str::to_string)
+}